@@ -0,0 +1,183 @@
+use std::{fmt, fs, path::Path};
+
+use cargo::core::Package;
+use regex::Regex;
+use semver::Version;
+
+/// Changelog file names looked up (case insensitively) in a package root
+const CHANGELOG_FILES: &[&str] = &["changelog.md", "changes.md", "releases.md", "history.md"];
+
+/// Where to read about the changes between the project version of a
+/// dependency and the one it would be upgraded to
+#[derive(Debug)]
+pub struct ReleaseNotes {
+    pub name: String,
+    pub from: Version,
+    pub to: Version,
+    pub repository: Option<String>,
+    pub homepage: Option<String>,
+    /// Changelog sections of the versions after the project one, up to and
+    /// including the upgraded one
+    pub changelog: Option<String>,
+}
+
+impl ReleaseNotes {
+    /// Release notes of `pkg`, the upgraded package, since version `from`
+    pub fn from_package(pkg: &Package, from: &Version) -> ReleaseNotes {
+        let metadata = pkg.manifest().metadata();
+        ReleaseNotes {
+            name: pkg.name().to_string(),
+            from: from.clone(),
+            to: pkg.version().clone(),
+            repository: metadata.repository.clone(),
+            homepage: metadata.homepage.clone(),
+            changelog: read_changelog(pkg.root())
+                .and_then(|changelog| extract_sections(&changelog, from, pkg.version())),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.repository.is_none() && self.homepage.is_none() && self.changelog.is_none()
+    }
+}
+
+impl fmt::Display for ReleaseNotes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} {} -> {}", self.name, self.from, self.to)?;
+        writeln!(f, "{}", "-".repeat(16))?;
+        if let Some(ref repository) = self.repository {
+            writeln!(f, "Repository: {repository}")?;
+        }
+        if let Some(ref homepage) = self.homepage {
+            writeln!(f, "Homepage: {homepage}")?;
+        }
+        if let Some(ref changelog) = self.changelog {
+            writeln!(f, "\n{changelog}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Read the changelog packaged in the crate at `root`, if any
+fn read_changelog(root: &Path) -> Option<String> {
+    fs::read_dir(root)
+        .ok()?
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            CHANGELOG_FILES.contains(&entry.file_name().to_string_lossy().to_lowercase().as_str())
+        })
+        .find_map(|entry| fs::read_to_string(entry.path()).ok())
+}
+
+/// Extract the sections of a markdown changelog whose heading names a version
+/// in `(from, to]`
+pub fn extract_sections(changelog: &str, from: &Version, to: &Version) -> Option<String> {
+    let version_pattern = Regex::new(r"\d+\.\d+\.\d+(-[0-9A-Za-z.-]+)?").unwrap();
+    let heading = |line: &str| -> Option<(usize, Option<Version>)> {
+        let level = line.chars().take_while(|&c| c == '#').count();
+        if level == 0 || !line[level..].starts_with(' ') {
+            return None;
+        }
+        let version = version_pattern
+            .find(line)
+            .and_then(|m| Version::parse(m.as_str()).ok());
+        Some((level, version))
+    };
+
+    // the level of headings introducing versions, e.g. `## [1.0.1] -
+    // 2020-01-01`
+    let version_level = changelog
+        .lines()
+        .filter_map(heading)
+        .find_map(|(level, version)| version.map(|_| level))?;
+
+    let mut sections = String::new();
+    let mut including = false;
+    for line in changelog.lines() {
+        if let Some((level, version)) = heading(line)
+            && level <= version_level
+        {
+            including = level == version_level
+                && version.is_some_and(|version| from < &version && &version <= to);
+        }
+        if including {
+            sections.push_str(line);
+            sections.push('\n');
+        }
+    }
+
+    let sections = sections.trim_end();
+    if sections.is_empty() {
+        None
+    } else {
+        Some(sections.to_owned())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    const CHANGELOG: &str = "# Changelog
+
+## [Unreleased]
+
+- Something in progress
+
+## [1.2.0] - 2024-03-01
+
+### Added
+
+- A feature
+
+## 1.1.0
+
+- A fix
+
+## v1.0.0
+
+- Initial release
+";
+
+    #[test]
+    fn extract() {
+        let sections =
+            extract_sections(CHANGELOG, &Version::new(1, 0, 0), &Version::new(1, 2, 0)).unwrap();
+        assert_eq!(
+            "## [1.2.0] - 2024-03-01
+
+### Added
+
+- A feature
+
+## 1.1.0
+
+- A fix",
+            sections
+        );
+    }
+
+    #[test]
+    fn extract_partial() {
+        let sections =
+            extract_sections(CHANGELOG, &Version::new(1, 0, 0), &Version::new(1, 1, 0)).unwrap();
+        assert_eq!("## 1.1.0\n\n- A fix", sections);
+    }
+
+    #[test]
+    fn extract_none() {
+        assert!(
+            extract_sections(CHANGELOG, &Version::new(1, 2, 0), &Version::new(1, 3, 0)).is_none()
+        );
+        assert!(
+            extract_sections(
+                "# Nothing here\n",
+                &Version::new(0, 1, 0),
+                &Version::new(1, 0, 0)
+            )
+            .is_none()
+        );
+    }
+}
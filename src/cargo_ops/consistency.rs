@@ -0,0 +1,233 @@
+use std::collections::BTreeMap;
+
+use cargo::util::CargoResult;
+use semver::{Op, Version, VersionReq};
+use serde::Serialize;
+
+use super::{
+    ElaborateWorkspace,
+    report::{or_none, print_json_line, print_section},
+};
+use crate::Options;
+
+/// The declaration of a dependency by a workspace member
+#[derive(Serialize)]
+pub struct Declaration {
+    pub member: String,
+    pub requirement: String,
+    pub resolved: String,
+}
+
+/// A registry dependency declared by several workspace members
+#[derive(Serialize)]
+pub struct SharedDependency {
+    pub name: String,
+    pub declarations: Vec<Declaration>,
+    /// The requirement to declare once in `[workspace.dependencies]` when
+    /// members disagree on the requirement or the resolved version
+    pub suggestion: Option<String>,
+}
+
+/// Registry dependencies declared by more than one member of the workspace
+/// of `ela`, along with their requirements and resolved versions, leaving
+/// out those ignored, of unchecked kinds or not matching `--packages`
+pub fn shared_dependencies(
+    ela: &ElaborateWorkspace<'_>,
+    options: &Options,
+) -> Vec<SharedDependency> {
+    let mut by_name: BTreeMap<String, Vec<(Version, Declaration)>> = BTreeMap::new();
+    for member in ela.workspace.members() {
+        let Some(deps) = ela.pkg_deps.get(&member.package_id()) else {
+            continue;
+        };
+        for (&id, dep) in deps {
+            if !id.source_id().is_registry()
+                || !options.includes_kind(dep.kind())
+                || !ela.reports_direct(member.package_id(), id)
+            {
+                continue;
+            }
+            by_name.entry(id.name().to_string()).or_default().push((
+                id.version().clone(),
+                Declaration {
+                    member: member.name().to_string(),
+                    requirement: dep.version_req().to_string(),
+                    resolved: id.version().to_string(),
+                },
+            ));
+        }
+    }
+    unify(by_name)
+}
+
+/// Dependencies declared by more than one member among the declarations
+/// `by_name`, with the requirement to unify them on if they disagree
+fn unify(by_name: BTreeMap<String, Vec<(Version, Declaration)>>) -> Vec<SharedDependency> {
+    by_name
+        .into_iter()
+        .filter(|(_, declarations)| declarations.len() > 1)
+        .map(|(name, mut declarations)| {
+            declarations.sort_by(|a, b| a.1.member.cmp(&b.1.member));
+            let consistent = declarations.windows(2).all(|pair| {
+                pair[0].1.requirement == pair[1].1.requirement && pair[0].0 == pair[1].0
+            });
+            // the requirement of the member resolving the newest version,
+            // the most demanding one if several members resolve it
+            let suggestion = if consistent {
+                None
+            } else {
+                declarations
+                    .iter()
+                    .max_by_key(|(version, declaration)| {
+                        (version.clone(), lower_bound(&declaration.requirement))
+                    })
+                    .map(|(_, declaration)| declaration.requirement.clone())
+            };
+            SharedDependency {
+                name,
+                declarations: declarations.into_iter().map(|(_, d)| d).collect(),
+                suggestion,
+            }
+        })
+        .collect()
+}
+
+/// The highest lower bound of `requirement`, if it parses
+fn lower_bound(requirement: &str) -> Option<Version> {
+    VersionReq::parse(requirement)
+        .ok()?
+        .comparators
+        .iter()
+        .filter(|c| !matches!(c.op, Op::Less | Op::LessEq))
+        .map(|c| Version::new(c.major, c.minor.unwrap_or(0), c.patch.unwrap_or(0)))
+        .max()
+}
+
+/// Print the shared dependencies section of the report, if any
+pub fn print_shared_dependencies(shared: &[SharedDependency]) -> CargoResult<()> {
+    if shared.is_empty() {
+        return Ok(());
+    }
+    print_section(
+        "Workspace requirements",
+        &["Name", "Member", "Requirement", "Resolved", "Suggested"],
+        shared.iter().flat_map(|dependency| {
+            dependency
+                .declarations
+                .iter()
+                .enumerate()
+                .map(move |(i, declaration)| {
+                    vec![
+                        dependency.name.clone(),
+                        declaration.member.clone(),
+                        declaration.requirement.clone(),
+                        declaration.resolved.clone(),
+                        or_none(dependency.suggestion.as_ref().filter(|_| i == 0)),
+                    ]
+                })
+        }),
+    )?;
+    let inconsistent: Vec<_> = shared
+        .iter()
+        .filter_map(|d| d.suggestion.as_ref().map(|s| (&d.name, s)))
+        .collect();
+    if !inconsistent.is_empty() {
+        println!("\nConsider unifying them in the workspace manifest:\n\n[workspace.dependencies]");
+        for (name, suggestion) in inconsistent {
+            println!("{name} = \"{suggestion}\"");
+        }
+    }
+    Ok(())
+}
+
+/// Print the shared dependencies as a JSON line, if any
+pub fn print_shared_dependencies_json(
+    shared: &[SharedDependency],
+    workspace_root: Option<&str>,
+) -> CargoResult<()> {
+    if !shared.is_empty() {
+        print_json_line("shared_dependencies", shared, workspace_root)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    fn declared(
+        declarations: &[(&str, &str, &str, &str)],
+    ) -> BTreeMap<String, Vec<(Version, Declaration)>> {
+        let mut by_name: BTreeMap<_, Vec<_>> = BTreeMap::new();
+        for &(name, member, requirement, resolved) in declarations {
+            by_name.entry(name.to_owned()).or_default().push((
+                Version::parse(resolved).unwrap(),
+                Declaration {
+                    member: member.to_owned(),
+                    requirement: requirement.to_owned(),
+                    resolved: resolved.to_owned(),
+                },
+            ));
+        }
+        by_name
+    }
+
+    #[test]
+    fn consistent() {
+        let shared = unify(declared(&[
+            ("log", "b", "0.4", "0.4.8"),
+            ("log", "a", "0.4", "0.4.8"),
+            ("rand", "a", "0.8", "0.8.5"),
+        ]));
+
+        assert_eq!(1, shared.len());
+        assert_eq!("log", shared[0].name);
+        let members: Vec<_> = shared[0].declarations.iter().map(|d| &d.member).collect();
+        assert_eq!(vec!["a", "b"], members);
+        assert_eq!(None, shared[0].suggestion);
+    }
+
+    #[test]
+    fn inconsistent() {
+        let shared = unify(declared(&[
+            // same requirement, different resolved versions
+            ("itoa", "a", "0.4", "0.4.8"),
+            ("itoa", "b", "0.4", "0.4.0"),
+            // newest resolved version wins
+            ("log", "a", "0.4.20", "0.4.20"),
+            ("log", "b", "0.4", "0.4.22"),
+            ("log", "c", "^0.4.1", "0.4.8"),
+            // the most demanding requirement among those resolving it
+            ("serde", "a", "1", "1.0.200"),
+            ("serde", "b", "1.0.150", "1.0.200"),
+        ]));
+        let suggestions: Vec<_> = shared
+            .iter()
+            .map(|d| (d.name.as_str(), d.suggestion.as_deref()))
+            .collect();
+
+        assert_eq!(
+            vec![
+                ("itoa", Some("0.4")),
+                ("log", Some("0.4")),
+                ("serde", Some("1.0.150")),
+            ],
+            suggestions
+        );
+    }
+
+    #[test]
+    fn lower_bounds() {
+        let bound = |requirement| lower_bound(requirement).map(|v| v.to_string());
+        assert_eq!(Some("1.2.0".to_owned()), bound("^1.2"));
+        assert_eq!(Some("1.2.3".to_owned()), bound("~1.2.3"));
+        assert_eq!(Some("1.2.3".to_owned()), bound("=1.2.3"));
+        assert_eq!(Some("0.4.0".to_owned()), bound("0.4"));
+        assert_eq!(None, bound("*"));
+        assert_eq!(Some("1.5.0".to_owned()), bound(">=1.2, >1.5, <2"));
+        assert_eq!(None, bound("<2"));
+        assert_eq!(None, bound("not a requirement"));
+    }
+}
@@ -0,0 +1,243 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use cargo::util::CargoResult;
+use semver::Version;
+use serde::Serialize;
+
+use super::{
+    ElaborateWorkspace,
+    report::{or_none, print_table},
+};
+
+/// A crate at a given version
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct CrateVersion {
+    pub name: String,
+    pub version: String,
+}
+
+/// A crate moving from one version to another
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct VersionChange {
+    pub name: String,
+    pub from: String,
+    pub to: String,
+}
+
+/// A crate resolved at several versions
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Duplicate {
+    pub name: String,
+    pub versions: Vec<String>,
+}
+
+/// The difference between the packages of two resolved workspaces
+#[derive(Debug, Default, PartialEq, Eq, Serialize)]
+pub struct PackageDelta {
+    /// Crates which are not used at all before
+    pub added: Vec<CrateVersion>,
+    /// Crates which are not used at all after
+    pub removed: Vec<CrateVersion>,
+    pub upgraded: Vec<VersionChange>,
+    pub downgraded: Vec<VersionChange>,
+    /// Versions of crates already used at other versions
+    pub added_versions: Vec<CrateVersion>,
+    /// Versions of crates still used at other versions
+    pub removed_versions: Vec<CrateVersion>,
+    /// Crates resolved at more versions than before
+    pub new_duplicates: Vec<Duplicate>,
+}
+
+impl PackageDelta {
+    /// The delta between the packages, other than workspace members, of
+    /// `from` and `to`
+    pub fn between(from: &ElaborateWorkspace<'_>, to: &ElaborateWorkspace<'_>) -> PackageDelta {
+        PackageDelta::from_versions(&package_versions(from), &package_versions(to))
+    }
+
+    /// The delta between versions of crates by name
+    pub fn from_versions(
+        from: &BTreeMap<String, BTreeSet<Version>>,
+        to: &BTreeMap<String, BTreeSet<Version>>,
+    ) -> PackageDelta {
+        let empty = BTreeSet::new();
+        let mut delta = PackageDelta::default();
+        let names: BTreeSet<_> = from.keys().chain(to.keys()).collect();
+        for name in names {
+            let before = from.get(name).unwrap_or(&empty);
+            let after = to.get(name).unwrap_or(&empty);
+            let crate_version = |version: &Version| CrateVersion {
+                name: name.clone(),
+                version: version.to_string(),
+            };
+            if before.is_empty() {
+                delta.added.extend(after.iter().map(crate_version));
+            } else if after.is_empty() {
+                delta.removed.extend(before.iter().map(crate_version));
+            } else if let ([old], [new]) = (
+                before.iter().collect::<Vec<_>>().as_slice(),
+                after.iter().collect::<Vec<_>>().as_slice(),
+            ) {
+                let change = VersionChange {
+                    name: name.clone(),
+                    from: old.to_string(),
+                    to: new.to_string(),
+                };
+                if new > old {
+                    delta.upgraded.push(change);
+                } else if new < old {
+                    delta.downgraded.push(change);
+                }
+            } else {
+                delta
+                    .added_versions
+                    .extend(after.difference(before).map(crate_version));
+                delta
+                    .removed_versions
+                    .extend(before.difference(after).map(crate_version));
+            }
+            if after.len() > 1 && after.len() > before.len() {
+                delta.new_duplicates.push(Duplicate {
+                    name: name.clone(),
+                    versions: after.iter().map(ToString::to_string).collect(),
+                });
+            }
+        }
+        delta
+    }
+
+    pub fn is_empty(&self) -> bool { self.count() == 0 }
+
+    /// Number of changes, new duplicates being counted by their added versions
+    pub fn count(&self) -> i32 {
+        (self.added.len()
+            + self.removed.len()
+            + self.upgraded.len()
+            + self.downgraded.len()
+            + self.added_versions.len()
+            + self.removed_versions.len()) as i32
+    }
+
+    /// Print the delta as a table, with `before` and `after` headings
+    pub fn print_list(&self, before: &str, after: &str) -> CargoResult<()> {
+        let row = |name: &str, before: Option<&str>, after: Option<&str>, change: &str| {
+            vec![
+                name.to_owned(),
+                or_none(before),
+                or_none(after),
+                change.to_owned(),
+            ]
+        };
+        let mut rows = BTreeSet::new();
+        for c in &self.added {
+            rows.insert(row(&c.name, None, Some(&c.version), "Added"));
+        }
+        for c in &self.added_versions {
+            rows.insert(row(&c.name, None, Some(&c.version), "Added version"));
+        }
+        for c in &self.removed {
+            rows.insert(row(&c.name, Some(&c.version), None, "Removed"));
+        }
+        for c in &self.removed_versions {
+            rows.insert(row(&c.name, Some(&c.version), None, "Removed version"));
+        }
+        for c in &self.upgraded {
+            rows.insert(row(&c.name, Some(&c.from), Some(&c.to), "Upgraded"));
+        }
+        for c in &self.downgraded {
+            rows.insert(row(&c.name, Some(&c.from), Some(&c.to), "Downgraded"));
+        }
+        print_table(&["Name", before, after, "Change"], rows)?;
+        for duplicate in &self.new_duplicates {
+            println!(
+                "{}: New duplicate: {}",
+                duplicate.name,
+                duplicate.versions.join(", ")
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Versions of the packages of `ela` by name, leaving out workspace members
+pub fn package_versions(ela: &ElaborateWorkspace<'_>) -> BTreeMap<String, BTreeSet<Version>> {
+    let mut versions: BTreeMap<String, BTreeSet<Version>> = BTreeMap::new();
+    for id in ela.pkgs.keys() {
+        if ela.workspace.members().any(|m| m.package_id() == *id) {
+            continue;
+        }
+        versions
+            .entry(id.name().to_string())
+            .or_default()
+            .insert(id.version().clone());
+    }
+    versions
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    fn versions(crates: &[(&str, &str)]) -> BTreeMap<String, BTreeSet<Version>> {
+        let mut versions: BTreeMap<String, BTreeSet<Version>> = BTreeMap::new();
+        for (name, version) in crates {
+            versions
+                .entry(name.to_string())
+                .or_default()
+                .insert(Version::parse(version).unwrap());
+        }
+        versions
+    }
+
+    fn crate_version(name: &str, version: &str) -> CrateVersion {
+        CrateVersion {
+            name: name.to_owned(),
+            version: version.to_owned(),
+        }
+    }
+
+    #[test]
+    fn delta() {
+        let delta = PackageDelta::from_versions(
+            &versions(&[
+                ("hyper", "0.14.28"),
+                ("log", "0.4.8"),
+                ("rand", "0.7.3"),
+                ("syn", "1.0.109"),
+                ("time", "0.3.36"),
+            ]),
+            &versions(&[
+                ("h2", "0.4.5"),
+                ("hyper", "1.4.1"),
+                ("log", "0.4.8"),
+                ("syn", "1.0.109"),
+                ("syn", "2.0.72"),
+                ("time", "0.3.20"),
+            ]),
+        );
+
+        assert_eq!(vec![crate_version("h2", "0.4.5")], delta.added);
+        assert_eq!(vec![crate_version("rand", "0.7.3")], delta.removed);
+        assert_eq!(
+            vec![VersionChange {
+                name: "hyper".to_owned(),
+                from: "0.14.28".to_owned(),
+                to: "1.4.1".to_owned(),
+            }],
+            delta.upgraded
+        );
+        assert_eq!(1, delta.downgraded.len());
+        assert_eq!(vec![crate_version("syn", "2.0.72")], delta.added_versions);
+        assert!(delta.removed_versions.is_empty());
+        assert_eq!(
+            vec![Duplicate {
+                name: "syn".to_owned(),
+                versions: vec!["1.0.109".to_owned(), "2.0.72".to_owned()],
+            }],
+            delta.new_duplicates
+        );
+        assert_eq!(5, delta.count());
+    }
+}
@@ -0,0 +1,179 @@
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+};
+
+use cargo::{
+    core::Workspace,
+    util::{CargoResult, context::GlobalContext},
+};
+use ignore::WalkBuilder;
+
+use crate::pattern::PatternSet;
+
+/// The workspaces found under a directory
+#[derive(Debug, Default)]
+pub struct WorkspaceRoots {
+    /// Root manifests of the workspaces
+    pub roots: Vec<PathBuf>,
+    /// Manifests which failed to load as part of a workspace, already warned
+    /// about
+    pub failed: Vec<PathBuf>,
+}
+
+/// The workspaces found under `dir`, skipping files ignored by git, build
+/// outputs and directories matching the patterns of `exclude_dirs` relative
+/// to `dir`
+pub fn workspace_roots(
+    dir: &Path,
+    exclude_dirs: &[String],
+    gctx: &GlobalContext,
+) -> CargoResult<WorkspaceRoots> {
+    let exclude_dirs = PatternSet::new(exclude_dirs)?;
+    let dir = dir.canonicalize()?;
+    let root = dir.clone();
+    let exclude = move |path: &Path| {
+        path.strip_prefix(&root)
+            .is_ok_and(|relative| exclude_dirs.matches(&relative.to_string_lossy()))
+    };
+    let walker = WalkBuilder::new(&dir)
+        // honour .gitignore files even outside of git repositories
+        .require_git(false)
+        .filter_entry(move |entry| {
+            let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+            !is_dir || (entry.file_name() != "target" && !exclude(entry.path()))
+        })
+        .build();
+
+    let mut roots = BTreeSet::new();
+    let mut failed = vec![];
+    for entry in walker {
+        let entry = entry?;
+        if entry.file_name() != "Cargo.toml" || !entry.file_type().is_some_and(|t| t.is_file()) {
+            continue;
+        }
+        match Workspace::new(entry.path(), gctx) {
+            Ok(workspace) => {
+                roots.insert(workspace.root_manifest().to_owned());
+            }
+            Err(e) => {
+                gctx.shell().warn(format!(
+                    "failed to load {}: {}",
+                    entry.path().to_string_lossy(),
+                    e
+                ))?;
+                failed.push(entry.into_path());
+            }
+        }
+    }
+    Ok(WorkspaceRoots {
+        roots: roots.into_iter().collect(),
+        failed,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::fs;
+
+    use pretty_assertions::assert_eq;
+
+    /// Write a package named after its directory, with `extra` manifest
+    /// sections
+    fn package(dir: &Path, extra: &str) {
+        let name = dir.file_name().unwrap().to_string_lossy();
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::write(dir.join("src/lib.rs"), "").unwrap();
+        fs::write(
+            dir.join("Cargo.toml"),
+            format!("[package]\nname = \"{name}\"\nversion = \"0.1.0\"\n\n{extra}"),
+        )
+        .unwrap();
+    }
+
+    /// Root manifests and manifests failing to load under `dir`, relative to
+    /// it
+    fn discover(dir: &Path, exclude_dirs: &[&str]) -> (Vec<PathBuf>, Vec<PathBuf>) {
+        let exclude_dirs: Vec<_> = exclude_dirs.iter().map(|d| d.to_string()).collect();
+        let gctx = GlobalContext::default().unwrap();
+        let dir = dir.canonicalize().unwrap();
+        let found = workspace_roots(&dir, &exclude_dirs, &gctx).unwrap();
+        let relative = |paths: Vec<PathBuf>| -> Vec<PathBuf> {
+            paths
+                .into_iter()
+                .map(|path| path.strip_prefix(&dir).unwrap().to_owned())
+                .collect()
+        };
+        (relative(found.roots), relative(found.failed))
+    }
+
+    fn roots(dir: &Path, exclude_dirs: &[&str]) -> Vec<PathBuf> { discover(dir, exclude_dirs).0 }
+
+    #[test]
+    fn nested_workspaces() {
+        let dir = tempfile::tempdir().unwrap();
+        package(
+            &dir.path().join("app"),
+            "[workspace]\nmembers = [\"member\"]\nexclude = [\"tools\"]\n",
+        );
+        package(&dir.path().join("app/member"), "");
+        // its own workspace, although under the directory of another
+        package(&dir.path().join("app/tools/gen"), "[workspace]\n");
+        package(&dir.path().join("single"), "");
+
+        assert_eq!(
+            vec![
+                PathBuf::from("app/Cargo.toml"),
+                PathBuf::from("app/tools/gen/Cargo.toml"),
+                PathBuf::from("single/Cargo.toml"),
+            ],
+            roots(dir.path(), &[])
+        );
+    }
+
+    #[test]
+    fn skipped_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        package(&dir.path().join("app"), "");
+        package(&dir.path().join("app/target/package/app"), "");
+        package(&dir.path().join("fixtures"), "");
+        package(&dir.path().join("fixtures/broken"), "");
+        package(&dir.path().join("vendor/log"), "");
+
+        assert_eq!(
+            vec![
+                PathBuf::from("app/Cargo.toml"),
+                PathBuf::from("fixtures/Cargo.toml"),
+            ],
+            roots(dir.path(), &["fixtures/*", "re:^vendor$"])
+        );
+        // build outputs are skipped even without exclusions
+        assert_eq!(
+            vec![
+                PathBuf::from("app/Cargo.toml"),
+                PathBuf::from("fixtures/Cargo.toml"),
+                PathBuf::from("fixtures/broken/Cargo.toml"),
+                PathBuf::from("vendor/log/Cargo.toml"),
+            ],
+            roots(dir.path(), &[])
+        );
+    }
+
+    #[test]
+    fn failed_manifests() {
+        let dir = tempfile::tempdir().unwrap();
+        package(&dir.path().join("app"), "");
+        fs::create_dir_all(dir.path().join("broken")).unwrap();
+        fs::write(dir.path().join("broken/Cargo.toml"), "[package\n").unwrap();
+
+        assert_eq!(
+            (
+                vec![PathBuf::from("app/Cargo.toml")],
+                vec![PathBuf::from("broken/Cargo.toml")]
+            ),
+            discover(dir.path(), &[])
+        );
+    }
+}
@@ -1,7 +1,7 @@
 use std::{
     cell::RefCell,
     cmp::Ordering,
-    collections::{BTreeSet, HashMap, HashSet, VecDeque},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
     io::{self, Write},
     rc::Rc,
 };
@@ -9,7 +9,7 @@ use std::{
 use anyhow::anyhow;
 use cargo::{
     core::{
-        Dependency, FeatureValue, Package, PackageId, Workspace,
+        Dependency, FeatureValue, Package, PackageId, Summary, Workspace,
         compiler::{CompileKind, RustcTargetData},
         dependency::DepKind,
         resolver::{
@@ -21,12 +21,23 @@ use cargo::{
     util::{CargoResult, context::GlobalContext, interning::InternedString},
 };
 use rustc_hash::FxHashMap;
+use semver::Version;
 use serde::{Deserialize, Serialize};
 use tabwriter::TabWriter;
 
-use crate::error::OutdatedError;
+use crate::{
+    error::OutdatedError,
+    pattern::{IgnoreSet, PatternSet},
+};
 
-use super::{Options, pkg_status::*};
+use super::{
+    Options,
+    changelog::ReleaseNotes,
+    grouped::OutdatedUse,
+    pkg_status::*,
+    report::print_json,
+    upgrade::{Selection, Upgrade},
+};
 
 /// An elaborate workspace containing resolved dependencies and
 /// the update status of packages
@@ -34,10 +45,21 @@ pub struct ElaborateWorkspace<'ela> {
     pub workspace: &'ela Workspace<'ela>,
     pub pkgs: FxHashMap<PackageId, Package>,
     pub pkg_deps: FxHashMap<PackageId, FxHashMap<PackageId, Dependency>>,
+    /// Features activated for each package by the resolve
+    pub pkg_features: FxHashMap<PackageId, Vec<InternedString>>,
+    /// `[patch]` entries not used by the resolve
+    pub unused_patches: Vec<PackageId>,
     /// Map of package status
     pub pkg_status: RefCell<FxHashMap<Vec<PackageId>, PkgStatus>>,
     /// Whether using workspace mode
     pub workspace_mode: bool,
+    /// Rules of dependencies to not print
+    ignore: IgnoreSet,
+    /// Patterns of packages to inspect, all packages if empty
+    packages: PatternSet,
+    /// Direct dependencies held back from their latest version as it is
+    /// unresolvable, along with what they conflict with
+    pub unresolvable: BTreeMap<String, String>,
 }
 
 /// A struct to serialize to json with serde
@@ -55,6 +77,18 @@ pub struct Metadata {
     pub latest: String,
     pub kind: Option<String>,
     pub platform: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repository: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub homepage: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub changelog: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conflicts_with: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compat_removed_because: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latest_removed_because: Option<String>,
 }
 
 impl Ord for Metadata {
@@ -81,8 +115,8 @@ impl<'ela> ElaborateWorkspace<'ela> {
 
         let cli_features = CliFeatures {
             features: Rc::new(flag_features),
-            all_features: options.all_features(),
-            uses_default_features: options.no_default_features(),
+            all_features: options.all_features,
+            uses_default_features: !options.no_default_features,
         };
 
         // The CompileKind, this has no target since it's the temp workspace
@@ -104,14 +138,21 @@ impl<'ela> ElaborateWorkspace<'ela> {
         let resolve = ws_resolve
             .workspace_resolve
             .expect("Error getting workspace resolved");
+        let package_ids: HashSet<PackageId> = packages.package_ids().collect();
         let mut pkgs = FxHashMap::default();
         let mut pkg_deps = FxHashMap::default();
+        let mut pkg_features = FxHashMap::default();
         for pkg in packages.get_many(packages.package_ids())? {
             let pkg_id = pkg.package_id();
             pkgs.insert(pkg_id, pkg.clone());
             let deps = pkg.dependencies();
             let mut dep_map = FxHashMap::default();
-            for dep_id in resolve.deps(pkg_id) {
+            // the workspace resolve also holds packages of features which
+            // are not activated, leave them out
+            for dep_id in resolve
+                .deps(pkg_id)
+                .filter(|dep_id| package_ids.contains(&dep_id.0))
+            {
                 for d in deps {
                     if d.matches_id(dep_id.0) {
                         dep_map.insert(dep_id.0, d.clone());
@@ -120,14 +161,20 @@ impl<'ela> ElaborateWorkspace<'ela> {
                 }
             }
             pkg_deps.insert(pkg_id, dep_map);
+            pkg_features.insert(pkg_id, resolve.features(pkg_id).to_vec());
         }
 
         Ok(ElaborateWorkspace {
             workspace,
             pkgs,
             pkg_deps,
+            pkg_features,
+            unused_patches: resolve.unused_patches().to_vec(),
             pkg_status: RefCell::new(FxHashMap::default()),
             workspace_mode: options.workspace || workspace.current().is_err(),
+            ignore: IgnoreSet::new(&options.ignore)?,
+            packages: PatternSet::new(&options.packages)?,
+            unresolvable: BTreeMap::new(),
         })
     }
 
@@ -157,6 +204,16 @@ impl<'ela> ElaborateWorkspace<'ela> {
         }
     }
 
+    /// The packages to report on, every member in workspace mode and the
+    /// root package otherwise
+    pub fn report_roots(&self, options: &Options) -> CargoResult<Vec<PackageId>> {
+        if self.workspace_mode {
+            Ok(self.workspace.members().map(|m| m.package_id()).collect())
+        } else {
+            Ok(vec![self.determine_root(options)?])
+        }
+    }
+
     /// Find a member based on member name
     fn find_member(&self, member: PackageId) -> CargoResult<PackageId> {
         for m in self.workspace.members() {
@@ -208,6 +265,59 @@ impl<'ela> ElaborateWorkspace<'ela> {
         ))
     }
 
+    /// Whether the last package of `path` is ignored in the report of its
+    /// first package
+    fn is_ignored(&self, path: &[PackageId]) -> bool {
+        let parent = if path.len() > 1 {
+            path.get(path.len() - 2)
+        } else {
+            None
+        };
+        match (path.first(), path.last()) {
+            (Some(member), Some(pkg)) => self.ignore.matches(
+                member.name().as_str(),
+                parent.map(|p| p.name().as_str()),
+                pkg.name().as_str(),
+            ),
+            _ => false,
+        }
+    }
+
+    /// Whether the direct dependency `dep` of `member` is reported, being
+    /// neither ignored nor left out by `--packages`
+    pub fn reports_direct(&self, member: PackageId, dep: PackageId) -> bool {
+        !self.is_ignored(&[member, dep])
+            && (self.packages.is_empty() || self.packages.matches(dep.name().as_str()))
+    }
+
+    /// Explain why `dep`, declared by `parent` in the project, is missing
+    /// from the dependencies of `other_parent`, the version of `parent`
+    /// resolved in this workspace
+    fn explain_removal(
+        &self,
+        project: &ElaborateWorkspace<'_>,
+        parent: PackageId,
+        other_parent: Option<PackageId>,
+        dep: &Dependency,
+    ) -> String {
+        let features = |ws: &ElaborateWorkspace<'_>, id: PackageId| {
+            ws.pkg_features
+                .get(&id)
+                .map(|features| features.join(", "))
+                .unwrap_or_default()
+        };
+        let other_pkg = other_parent.and_then(|id| self.pkgs.get(&id));
+        removal_reason(
+            dep,
+            parent,
+            other_pkg.map(Package::summary),
+            &features(project, parent),
+            &other_pkg
+                .map(|pkg| features(self, pkg.package_id()))
+                .unwrap_or_default(),
+        )
+    }
+
     /// Resolve compatible and latest status from the corresponding
     /// `ElaborateWorkspace`s
     pub fn resolve_status(
@@ -230,14 +340,38 @@ impl<'ela> ElaborateWorkspace<'ela> {
         };
 
         let mut queue = VecDeque::new();
-        queue.push_back((vec![root], Some(compat_root), Some(latest_root)));
-        while let Some((path, compat_pkg, latest_pkg)) = queue.pop_front() {
+        queue.push_back((vec![root], Some(compat_root), Some(latest_root), None, None));
+        while let Some((path, compat_pkg, latest_pkg, compat_removal, latest_removal)) =
+            queue.pop_front()
+        {
             let pkg = path.last().ok_or(OutdatedError::EmptyPath)?;
             let depth = path.len() as i32 - 1;
             // generate pkg_status
+            let compat_status =
+                Status::from_versions(pkg.version(), compat_pkg.map(PackageId::version));
+            let latest_status = match latest.unresolvable.get(pkg.name().as_str()) {
+                Some(conflict) if depth == 1 => Status::Unresolvable(conflict.clone()),
+                _ => Status::from_versions(pkg.version(), latest_pkg.map(PackageId::version)),
+            };
+            // prefer the notes of the latest version, which cover the compat one
+            let release_notes = if options.changelog && depth == 1 {
+                let upgraded = match (&latest_status, &compat_status) {
+                    (Status::Version(_), _) => latest_pkg.and_then(|id| latest.pkgs.get(&id)),
+                    (_, Status::Version(_)) => compat_pkg.and_then(|id| compat.pkgs.get(&id)),
+                    _ => None,
+                };
+                upgraded
+                    .map(|upgraded| ReleaseNotes::from_package(upgraded, pkg.version()))
+                    .filter(|notes| !notes.is_empty())
+            } else {
+                None
+            };
             let status = PkgStatus {
-                compat: Status::from_versions(pkg.version(), compat_pkg.map(PackageId::version)),
-                latest: Status::from_versions(pkg.version(), latest_pkg.map(PackageId::version)),
+                compat: compat_status,
+                latest: latest_status,
+                release_notes,
+                compat_removal,
+                latest_removal,
             };
             debug!(
                 _context,
@@ -252,10 +386,12 @@ impl<'ela> ElaborateWorkspace<'ela> {
             // next layer
             // this unwrap is safe since we first check if it is None :)
             if options.depth.is_none() || depth < options.depth.unwrap() {
+                let (parent_compat, parent_latest) = (compat_pkg, latest_pkg);
                 self.pkg_deps[pkg]
                     .keys()
                     .filter(|dep| !path.contains(dep))
                     .filter(|&dep| !skip.contains(dep.name().as_str()))
+                    .filter(|&dep| options.includes_kind(self.pkg_deps[pkg][dep].kind()))
                     .for_each(|&dep| {
                         let name = dep.name();
                         let compat_pkg = compat_pkg
@@ -268,9 +404,28 @@ impl<'ela> ElaborateWorkspace<'ela> {
                             .map(HashMap::keys)
                             .and_then(|mut deps| deps.find(|dep| dep.name() == name))
                             .cloned();
+                        let (compat_removal, latest_removal) = if options.explain {
+                            let declaration = &self.pkg_deps[pkg][&dep];
+                            (
+                                compat_pkg.is_none().then(|| {
+                                    compat.explain_removal(self, *pkg, parent_compat, declaration)
+                                }),
+                                latest_pkg.is_none().then(|| {
+                                    latest.explain_removal(self, *pkg, parent_latest, declaration)
+                                }),
+                            )
+                        } else {
+                            (None, None)
+                        };
                         let mut path = path.clone();
                         path.push(dep);
-                        queue.push_back((path, compat_pkg, latest_pkg));
+                        queue.push_back((
+                            path,
+                            compat_pkg,
+                            latest_pkg,
+                            compat_removal,
+                            latest_removal,
+                        ));
                     });
             }
         }
@@ -278,65 +433,91 @@ impl<'ela> ElaborateWorkspace<'ela> {
         Ok(())
     }
 
-    /// Print package status to `TabWriter`
-    pub fn print_list(
-        &'ela self,
+    /// Outdated direct dependencies of the workspace member `root` according
+    /// to its resolved status
+    pub fn direct_upgrades(&self, root: PackageId) -> Vec<Upgrade> {
+        if !self.workspace.members().any(|m| m.package_id() == root) {
+            return vec![];
+        }
+        let pkg_status = self.pkg_status.borrow();
+        let mut upgrades: Vec<_> = pkg_status
+            .iter()
+            .filter(|(path, _)| {
+                path.len() == 2 && path[0] == root && self.reports_direct(root, path[1])
+            })
+            .filter(|(_, status)| {
+                status.compat.version().is_some() || status.latest.version().is_some()
+            })
+            .map(|(path, status)| {
+                let dependency = &self.pkg_deps[&root][&path[1]];
+                Upgrade {
+                    member: root.name().to_string(),
+                    manifest_path: self.pkgs[&root].manifest_path().to_owned(),
+                    name: dependency.name_in_toml().to_string(),
+                    package: path[1].name().to_string(),
+                    kind: dependency.kind(),
+                    platform: dependency.platform().map(ToString::to_string),
+                    project: path[1].version().clone(),
+                    compat: status.compat.version().cloned(),
+                    latest: status.latest.version().cloned(),
+                    selection: Selection::Keep,
+                }
+            })
+            .collect();
+        upgrades.sort_by(|a, b| a.name.cmp(&b.name));
+        upgrades
+    }
+
+    /// Registry packages with a compatible update according to the resolved
+    /// status of `root`, along with the compatible version, when their
+    /// parent allows it so the update only touches the lock file
+    pub fn compat_updates(&self, root: PackageId) -> Vec<(PackageId, Version)> {
+        self.pkg_status
+            .borrow()
+            .iter()
+            .filter(|(path, _)| path.len() > 1 && path[0] == root && !self.is_ignored(path))
+            .filter_map(|(path, status)| {
+                let [.., parent, id] = path.as_slice() else {
+                    return None;
+                };
+                let version = status.compat.version()?;
+                self.pkg_deps[parent][id]
+                    .version_req()
+                    .matches(version)
+                    .then(|| (*id, version.clone()))
+            })
+            .filter(|(id, _)| id.source_id().is_registry())
+            .filter(|(id, _)| self.packages.is_empty() || self.packages.matches(id.name().as_str()))
+            .collect()
+    }
+
+    /// Walk the dependency tree of `root` breadth first, down to the depth
+    /// requested, calling `f` with the path to each outdated package along
+    /// with its status
+    fn visit_outdated<F>(
+        &self,
         options: &Options,
         root: PackageId,
-        preceding_line: bool,
         skip: &HashSet<String>,
-    ) -> CargoResult<i32> {
-        let mut lines = BTreeSet::new();
+        mut f: F,
+    ) -> CargoResult<()>
+    where
+        F: FnMut(&[PackageId], &PkgStatus) -> CargoResult<()>,
+    {
         let mut queue = VecDeque::new();
         queue.push_back(vec![root]);
         while let Some(path) = queue.pop_front() {
             let pkg = path.last().ok_or(OutdatedError::EmptyPath)?;
-            let name = pkg.name().to_string();
-
-            if options.ignore.contains(&name) {
+            if self.is_ignored(&path) {
                 continue;
             }
 
             let depth = path.len() as i32 - 1;
-            // generate lines
-            let status = &self.pkg_status.borrow_mut()[&path];
+            let status = &self.pkg_status.borrow()[&path];
             if (status.compat.is_changed() || status.latest.is_changed())
-                && (options.packages.is_empty() || options.packages.contains(&name))
+                && (self.packages.is_empty() || self.packages.matches(pkg.name().as_str()))
             {
-                // name version compatible latest kind platform
-                let parent = path.get(path.len() - 2);
-                if let Some(parent) = parent {
-                    let dependency = &self.pkg_deps[parent][pkg];
-                    let label = if self.workspace_mode
-                        || parent == &self.workspace.current()?.package_id()
-                    {
-                        name
-                    } else {
-                        format!("{}->{}", self.pkgs[parent].name(), name)
-                    };
-                    let line = format!(
-                        "{}\t{}\t{}\t{}\t{:?}\t{}\n",
-                        label,
-                        pkg.version(),
-                        status.compat,
-                        status.latest,
-                        dependency.kind(),
-                        dependency
-                            .platform()
-                            .map(ToString::to_string)
-                            .unwrap_or_else(|| "---".to_owned())
-                    );
-                    lines.insert(line);
-                } else {
-                    let line = format!(
-                        "{}\t{}\t{}\t{}\t---\t---\n",
-                        name,
-                        pkg.version(),
-                        status.compat,
-                        status.latest
-                    );
-                    lines.insert(line);
-                }
+                f(&path, status)?;
             }
             // next layer
             // this unwrap is safe since we first check if it is None :)
@@ -349,6 +530,7 @@ impl<'ela> ElaborateWorkspace<'ela> {
                             || !self.workspace.members().any(|mem| &mem.package_id() == dep)
                     })
                     .filter(|&dep| !skip.contains(dep.name().as_str()))
+                    .filter(|&dep| options.includes_kind(self.pkg_deps[pkg][dep].kind()))
                     .for_each(|&dep| {
                         let mut path = path.clone();
                         path.push(dep);
@@ -356,6 +538,108 @@ impl<'ela> ElaborateWorkspace<'ela> {
                     });
             }
         }
+        Ok(())
+    }
+
+    /// Label of the outdated package at the end of `path`, qualified by its
+    /// parent unless the parent is the member being reported
+    fn label(&self, path: &[PackageId]) -> CargoResult<String> {
+        let pkg = path.last().ok_or(OutdatedError::EmptyPath)?;
+        let name = pkg.name().to_string();
+        match path {
+            [.., parent, _]
+                if !self.workspace_mode && parent != &self.workspace.current()?.package_id() =>
+            {
+                Ok(format!("{}->{}", self.pkgs[parent].name(), name))
+            }
+            _ => Ok(name),
+        }
+    }
+
+    /// Outdated dependencies of `root` according to its resolved status,
+    /// along with the kind of dependency pulling each of them in
+    pub fn outdated_uses(
+        &self,
+        options: &Options,
+        root: PackageId,
+        skip: &HashSet<String>,
+    ) -> CargoResult<Vec<OutdatedUse>> {
+        let mut uses = vec![];
+        self.visit_outdated(options, root, skip, |path, status| {
+            if let [.., parent, pkg] = path {
+                let kind = match self.pkg_deps[parent][pkg].kind() {
+                    DepKind::Normal => "Normal",
+                    DepKind::Development => "Development",
+                    DepKind::Build => "Build",
+                };
+                uses.push(OutdatedUse {
+                    name: pkg.name().to_string(),
+                    project: pkg.version().to_string(),
+                    compat: status.compat.to_string(),
+                    latest: status.latest.to_string(),
+                    member: root.name().to_string(),
+                    kind: kind.to_owned(),
+                });
+            }
+            Ok(())
+        })?;
+        Ok(uses)
+    }
+
+    /// Print package status to `TabWriter`
+    pub fn print_list(
+        &'ela self,
+        options: &Options,
+        root: PackageId,
+        preceding_line: bool,
+        skip: &HashSet<String>,
+    ) -> CargoResult<i32> {
+        let mut lines = BTreeSet::new();
+        let mut release_notes = BTreeMap::new();
+        let mut unresolvable = BTreeMap::new();
+        let mut removals = BTreeSet::new();
+        self.visit_outdated(options, root, skip, |path, status| {
+            let label = self.label(path)?;
+            // name version compatible latest kind platform
+            if let [.., parent, pkg] = path {
+                let dependency = &self.pkg_deps[parent][pkg];
+                let line = format!(
+                    "{}\t{}\t{}\t{}\t{:?}\t{}\n",
+                    label,
+                    pkg.version(),
+                    status.compat,
+                    status.latest,
+                    dependency.kind(),
+                    dependency
+                        .platform()
+                        .map(ToString::to_string)
+                        .unwrap_or_else(|| "---".to_owned())
+                );
+                lines.insert(line);
+                if let Status::Unresolvable(ref conflict) = status.latest {
+                    unresolvable.insert(label.clone(), conflict.clone());
+                }
+                if let Some(ref reason) = status.compat_removal {
+                    removals.insert(format!("{label}: Compat removed: {reason}"));
+                }
+                if let Some(ref reason) = status.latest_removal {
+                    removals.insert(format!("{label}: Latest removed: {reason}"));
+                }
+                if let Some(ref notes) = status.release_notes {
+                    release_notes.insert(label, notes.to_string());
+                }
+            } else {
+                let line = format!(
+                    "{}\t{}\t{}\t{}\t---\t---\n",
+                    label,
+                    root.version(),
+                    status.compat,
+                    status.latest
+                );
+                lines.insert(line);
+            }
+            Ok(())
+        })?;
 
         if lines.is_empty() {
             if !self.workspace_mode {
@@ -376,6 +660,18 @@ impl<'ela> ElaborateWorkspace<'ela> {
             }
             tw.flush()?;
             write!(io::stdout(), "{}", String::from_utf8(tw.into_inner()?)?)?;
+            for (label, conflict) in &unresolvable {
+                writeln!(
+                    io::stdout(),
+                    "{label}: Latest unresolvable: conflicts with {conflict}"
+                )?;
+            }
+            for removal in &removals {
+                writeln!(io::stdout(), "{removal}")?;
+            }
+            for notes in release_notes.values() {
+                write!(io::stdout(), "\n{notes}")?;
+            }
             io::stdout().flush()?;
         }
 
@@ -392,92 +688,223 @@ impl<'ela> ElaborateWorkspace<'ela> {
             crate_name: root.name().to_string(),
             dependencies: BTreeSet::new(),
         };
-        let mut queue = VecDeque::new();
-        queue.push_back(vec![root]);
+        self.visit_outdated(options, root, skip, |path, status| {
+            let label = self.label(path)?;
+            // name version compatible latest kind platform
+            let line = if let [.., parent, pkg] = path {
+                let dependency = &self.pkg_deps[parent][pkg];
+                let notes = status.release_notes.as_ref();
+                let dependency_type = match dependency.kind() {
+                    DepKind::Normal => "Normal",
+                    DepKind::Development => "Development",
+                    DepKind::Build => "Build",
+                };
 
-        while let Some(path) = queue.pop_front() {
-            let pkg = path.last().ok_or(OutdatedError::EmptyPath)?;
-            let name = pkg.name().to_string();
+                Metadata {
+                    name: label,
+                    project: pkg.version().to_string(),
+                    compat: status.compat.to_string(),
+                    latest: status.latest.to_string(),
+                    kind: Some(dependency_type.to_string()),
+                    platform: dependency.platform().map(|p| p.to_string()),
+                    repository: notes.and_then(|n| n.repository.clone()),
+                    homepage: notes.and_then(|n| n.homepage.clone()),
+                    changelog: notes.and_then(|n| n.changelog.clone()),
+                    conflicts_with: match status.latest {
+                        Status::Unresolvable(ref conflict) => Some(conflict.clone()),
+                        _ => None,
+                    },
+                    compat_removed_because: status.compat_removal.clone(),
+                    latest_removed_because: status.latest_removal.clone(),
+                }
+            } else {
+                Metadata {
+                    name: label,
+                    project: root.version().to_string(),
+                    compat: status.compat.to_string(),
+                    latest: status.latest.to_string(),
+                    kind: None,
+                    platform: None,
+                    repository: None,
+                    homepage: None,
+                    changelog: None,
+                    conflicts_with: None,
+                    compat_removed_because: None,
+                    latest_removed_because: None,
+                }
+            };
 
-            if options.ignore.contains(&name) {
-                continue;
-            }
+            crate_graph.dependencies.insert(line);
+            Ok(())
+        })?;
 
-            let depth = path.len() as i32 - 1;
-            // generate lines
-            let status = &self.pkg_status.borrow_mut()[&path];
-            if (status.compat.is_changed() || status.latest.is_changed())
-                && (options.packages.is_empty() || options.packages.contains(&name))
-            {
-                // name version compatible latest kind platform
-                // safely get the parent index
-                let parent = if path.len() > 1 {
-                    path.get(path.len() - 2)
-                } else {
-                    None
-                };
+        print_json(&crate_graph, options.workspace_root.as_deref())?;
 
-                let line = if let Some(parent) = parent {
-                    let dependency = &self.pkg_deps[parent][pkg];
-                    let label = if self.workspace_mode
-                        || parent == &self.workspace.current()?.package_id()
-                    {
-                        name
-                    } else {
-                        format!("{}->{}", self.pkgs[parent].name(), name)
-                    };
-
-                    let dependency_type = match dependency.kind() {
-                        DepKind::Normal => "Normal",
-                        DepKind::Development => "Development",
-                        DepKind::Build => "Build",
-                    };
-
-                    Metadata {
-                        name: label,
-                        project: pkg.version().to_string(),
-                        compat: status.compat.to_string(),
-                        latest: status.latest.to_string(),
-                        kind: Some(dependency_type.to_string()),
-                        platform: dependency.platform().map(|p| p.to_string()),
-                    }
-                } else {
-                    Metadata {
-                        name,
-                        project: pkg.version().to_string(),
-                        compat: status.compat.to_string(),
-                        latest: status.latest.to_string(),
-                        kind: None,
-                        platform: None,
-                    }
-                };
+        Ok(crate_graph.dependencies.len() as i32)
+    }
+}
 
-                crate_graph.dependencies.insert(line);
-            }
-            // next layer
-            // this unwrap is safe since we first check if it is None :)
-            if options.depth.is_none() || depth < options.depth.unwrap() {
-                self.pkg_deps[pkg]
-                    .keys()
-                    .filter(|dep| !path.contains(dep))
-                    .filter(|dep| {
-                        !self.workspace_mode
-                            || !self
-                                .workspace
-                                .members()
-                                .any(|mem| mem.package_id() == **dep)
-                    })
-                    .filter(|&dep| !skip.contains(dep.name().as_str()))
-                    .for_each(|dep| {
-                        let mut path = path.clone();
-                        path.push(*dep);
-                        queue.push_back(path);
-                    });
-            }
-        }
+/// Explain why `dep`, declared by `parent`, is missing from the dependencies
+/// of `other_parent`, given the features activated for both
+fn removal_reason(
+    dep: &Dependency,
+    parent: PackageId,
+    other_parent: Option<&Summary>,
+    features_before: &str,
+    features_after: &str,
+) -> String {
+    let Some(other_parent) = other_parent else {
+        return format!("its parent {} is removed", parent.name());
+    };
+    let label = format!(
+        "{} {}",
+        other_parent.package_id().name(),
+        other_parent.package_id().version()
+    );
+    let declarations: Vec<_> = other_parent
+        .dependencies()
+        .iter()
+        .filter(|d| d.package_name() == dep.package_name())
+        .collect();
+    if declarations.is_empty() {
+        return match other_parent
+            .dependencies()
+            .iter()
+            .find(|d| d.name_in_toml() == dep.name_in_toml())
+        {
+            Some(renamed) => format!(
+                "{} is renamed to {} by {}",
+                dep.name_in_toml(),
+                renamed.package_name(),
+                label
+            ),
+            None => format!("dropped by {label}"),
+        };
+    }
+    if dep.kind() != DepKind::Development
+        && declarations
+            .iter()
+            .all(|d| d.kind() == DepKind::Development)
+    {
+        return format!("only a dev-dependency of {label}");
+    }
+    if declarations
+        .iter()
+        .all(|d| d.platform().is_some() && d.platform() != dep.platform())
+    {
+        let platforms: Vec<_> = declarations
+            .iter()
+            .filter_map(|d| d.platform())
+            .map(ToString::to_string)
+            .collect();
+        return format!("only declared for {} by {}", platforms.join(", "), label);
+    }
+    if declarations.iter().all(|d| d.is_optional()) {
+        return if features_before == features_after {
+            format!("optional in {label} and not enabled by its features [{features_after}]")
+        } else {
+            format!(
+                "optional in {label} and not enabled by its features [{features_after}], \
+                 previously [{features_before}]"
+            )
+        };
+    }
+    format!("declared by {label} but not resolved")
+}
 
-        println!("{}", serde_json::to_string(&crate_graph)?);
+#[cfg(test)]
+mod test {
+    use super::*;
 
-        Ok(crate_graph.dependencies.len() as i32)
+    use cargo::core::SourceId;
+    use cargo_platform::Platform;
+    use pretty_assertions::assert_eq;
+
+    fn crates_io() -> SourceId {
+        SourceId::from_url("registry+https://github.com/rust-lang/crates.io-index").unwrap()
+    }
+
+    fn dependency(name: &str) -> Dependency {
+        Dependency::parse(name, Some("1"), crates_io()).unwrap()
+    }
+
+    fn summary(dependencies: Vec<Dependency>) -> Summary {
+        let id = PackageId::try_new("parent", "2.0.0", crates_io()).unwrap();
+        Summary::new(id, dependencies, &BTreeMap::new(), None::<&str>, None).unwrap()
+    }
+
+    fn reason(dep: &Dependency, other_parent: Option<&Summary>) -> String {
+        let parent = PackageId::try_new("parent", "1.0.0", crates_io()).unwrap();
+        removal_reason(dep, parent, other_parent, "std", "std")
+    }
+
+    #[test]
+    fn removal_reasons() {
+        let dep = dependency("log");
+        assert_eq!("its parent parent is removed", reason(&dep, None));
+        assert_eq!(
+            "dropped by parent 2.0.0",
+            reason(&dep, Some(&summary(vec![dependency("rand")])))
+        );
+        assert_eq!(
+            "declared by parent 2.0.0 but not resolved",
+            reason(&dep, Some(&summary(vec![dependency("log")])))
+        );
+
+        let mut dev = dependency("log");
+        dev.set_kind(DepKind::Development);
+        assert_eq!(
+            "only a dev-dependency of parent 2.0.0",
+            reason(&dep, Some(&summary(vec![dev.clone()])))
+        );
+        // still declared for the dev-dependencies it was
+        assert_eq!(
+            "declared by parent 2.0.0 but not resolved",
+            reason(&dev, Some(&summary(vec![dev.clone()])))
+        );
+    }
+
+    #[test]
+    fn renamed_removal() {
+        let mut dep = dependency("rand_core");
+        dep.set_explicit_name_in_toml("rand");
+        let mut renamed = dependency("rand_core_ng");
+        renamed.set_explicit_name_in_toml("rand");
+        assert_eq!(
+            "rand is renamed to rand_core_ng by parent 2.0.0",
+            reason(&dep, Some(&summary(vec![renamed])))
+        );
+    }
+
+    #[test]
+    fn target_removal() {
+        let dep = dependency("winapi");
+        let mut windows = dependency("winapi");
+        windows.set_platform(Some("cfg(windows)".parse::<Platform>().unwrap()));
+        assert_eq!(
+            "only declared for cfg(windows) by parent 2.0.0",
+            reason(&dep, Some(&summary(vec![windows.clone()])))
+        );
+        assert_eq!(
+            "declared by parent 2.0.0 but not resolved",
+            reason(&windows, Some(&summary(vec![windows.clone()])))
+        );
+    }
+
+    #[test]
+    fn feature_removal() {
+        let dep = dependency("serde");
+        let mut optional = dependency("serde");
+        optional.set_optional(true);
+        let other_parent = summary(vec![optional]);
+        let parent = PackageId::try_new("parent", "1.0.0", crates_io()).unwrap();
+        assert_eq!(
+            "optional in parent 2.0.0 and not enabled by its features [std]",
+            removal_reason(&dep, parent, Some(&other_parent), "std", "std")
+        );
+        assert_eq!(
+            "optional in parent 2.0.0 and not enabled by its features [], previously [serde, std]",
+            removal_reason(&dep, parent, Some(&other_parent), "serde, std", "")
+        );
     }
 }
@@ -0,0 +1,123 @@
+use std::collections::HashSet;
+
+use anyhow::anyhow;
+use cargo::util::CargoResult;
+use toml::{Value, value::Table};
+
+/// Whether enabling `requested` features activates the optional dependency
+/// `name` (as written in the manifest), following the `[features]` table
+/// with Cargo's semantics:
+/// - `dep:name` activates the dependency without an implicit feature
+/// - `name` activates it through its implicit feature, which only exists
+///   when no `dep:name` is used
+/// - `name/feature` activates it along with one of its features
+/// - `name?/feature` only enables the feature if it is otherwise activated
+pub fn activates_dependency(
+    features_table: &Option<Value>,
+    requested: &[&str],
+    name: &str,
+) -> CargoResult<bool> {
+    let empty = Table::new();
+    let features_table = match *features_table {
+        Some(Value::Table(ref features_table)) => features_table,
+        Some(_) => return Err(anyhow!("[features] is not a table")),
+        None => &empty,
+    };
+    let explicit = format!("dep:{name}");
+    let implicit_feature = !features_table
+        .values()
+        .filter_map(Value::as_array)
+        .flatten()
+        .any(|value| value.as_str() == Some(explicit.as_str()));
+
+    let mut to_resolve: Vec<&str> = requested
+        .iter()
+        .copied()
+        .filter(|f| !f.is_empty())
+        .collect();
+    let mut visited: HashSet<&str> = HashSet::new();
+    while let Some(feature) = to_resolve.pop() {
+        if !visited.insert(feature) {
+            continue;
+        }
+        if let Some(dep) = feature.strip_prefix("dep:") {
+            if dep == name {
+                return Ok(true);
+            }
+            continue;
+        }
+        if let Some((dep, _)) = feature.split_once('/') {
+            // weak `name?/feature` values keep the `?` and never match
+            if dep == name {
+                return Ok(true);
+            }
+            continue;
+        }
+        if feature == name && implicit_feature {
+            return Ok(true);
+        }
+        match features_table.get(feature) {
+            Some(Value::Array(values)) => {
+                for value in values {
+                    match value.as_str() {
+                        Some(value) => to_resolve.push(value),
+                        None => {
+                            return Err(anyhow!(
+                                "feature `{}` contains a value which is not a string",
+                                feature
+                            ));
+                        }
+                    }
+                }
+            }
+            Some(_) => return Err(anyhow!("feature `{}` is not mapped to an array", feature)),
+            None => {}
+        }
+    }
+    Ok(false)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn features(toml: &str) -> Option<Value> { Some(Value::Table(toml.parse().unwrap())) }
+
+    #[test]
+    fn implicit() {
+        let table = features("default = ['std']\nstd = ['serde']");
+        assert!(activates_dependency(&table, &["default"], "serde").unwrap());
+        assert!(activates_dependency(&table, &["serde"], "serde").unwrap());
+        assert!(!activates_dependency(&table, &["std"], "log").unwrap());
+        assert!(activates_dependency(&None, &["serde"], "serde").unwrap());
+    }
+
+    #[test]
+    fn explicit() {
+        let table = features("ser = ['dep:serde']\nserde = []");
+        assert!(activates_dependency(&table, &["ser"], "serde").unwrap());
+        // the feature named after the dependency does not activate it
+        assert!(!activates_dependency(&table, &["serde"], "serde").unwrap());
+    }
+
+    #[test]
+    fn dependency_features() {
+        let table = features("derive = ['serde/derive']\nweak = ['log?/std']");
+        assert!(activates_dependency(&table, &["derive"], "serde").unwrap());
+        assert!(!activates_dependency(&table, &["weak"], "log").unwrap());
+        assert!(activates_dependency(&table, &["log/std"], "log").unwrap());
+    }
+
+    #[test]
+    fn cycle() {
+        let table = features("a = ['b']\nb = ['a']");
+        assert!(!activates_dependency(&table, &["a"], "serde").unwrap());
+    }
+
+    #[test]
+    fn malformed() {
+        assert!(activates_dependency(&features("a = 'b'"), &["a"], "serde").is_err());
+        assert!(activates_dependency(&features("a = [1]"), &["a"], "serde").is_err());
+        assert!(activates_dependency(&Some(Value::Integer(1)), &["a"], "serde").is_err());
+    }
+}
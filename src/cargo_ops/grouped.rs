@@ -0,0 +1,149 @@
+use std::collections::BTreeMap;
+
+use cargo::util::CargoResult;
+use serde::Serialize;
+
+use super::report::{print_json_line, print_table};
+
+/// An outdated dependency as used by a workspace member
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutdatedUse {
+    pub name: String,
+    pub project: String,
+    pub compat: String,
+    pub latest: String,
+    pub member: String,
+    pub kind: String,
+}
+
+/// A workspace member using an outdated dependency
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct MemberUse {
+    pub member: String,
+    pub kind: String,
+}
+
+/// An outdated dependency along with the workspace members using it
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct DependencyGroup {
+    pub name: String,
+    pub project: String,
+    pub compat: String,
+    pub latest: String,
+    pub used_by: Vec<MemberUse>,
+}
+
+/// Merge the uses of the same outdated dependency by several members
+pub fn group_by_dependency(uses: Vec<OutdatedUse>) -> Vec<DependencyGroup> {
+    let mut groups: BTreeMap<_, Vec<MemberUse>> = BTreeMap::new();
+    for outdated in uses {
+        let used_by = groups
+            .entry((
+                outdated.name,
+                outdated.project,
+                outdated.compat,
+                outdated.latest,
+            ))
+            .or_default();
+        let member_use = MemberUse {
+            member: outdated.member,
+            kind: outdated.kind,
+        };
+        if !used_by.contains(&member_use) {
+            used_by.push(member_use);
+        }
+    }
+    groups
+        .into_iter()
+        .map(|((name, project, compat, latest), mut used_by)| {
+            used_by.sort();
+            DependencyGroup {
+                name,
+                project,
+                compat,
+                latest,
+                used_by,
+            }
+        })
+        .collect()
+}
+
+/// Print each outdated dependency once, returning their number
+pub fn print_groups(groups: &[DependencyGroup]) -> CargoResult<i32> {
+    if groups.is_empty() {
+        println!("All dependencies are up to date, yay!");
+        return Ok(0);
+    }
+    print_table(
+        &["Name", "Project", "Compat", "Latest", "Used by"],
+        groups.iter().map(|group| {
+            let used_by: Vec<_> = group
+                .used_by
+                .iter()
+                .map(|u| format!("{} ({})", u.member, u.kind))
+                .collect();
+            vec![
+                group.name.clone(),
+                group.project.clone(),
+                group.compat.clone(),
+                group.latest.clone(),
+                used_by.join(", "),
+            ]
+        }),
+    )?;
+    Ok(groups.len() as i32)
+}
+
+/// Print the outdated dependencies as a JSON line, returning their number
+pub fn print_groups_json(
+    groups: &[DependencyGroup],
+    workspace_root: Option<&str>,
+) -> CargoResult<i32> {
+    print_json_line("dependencies", groups, workspace_root)?;
+    Ok(groups.len() as i32)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    fn outdated(name: &str, project: &str, member: &str, kind: &str) -> OutdatedUse {
+        OutdatedUse {
+            name: name.to_owned(),
+            project: project.to_owned(),
+            compat: "---".to_owned(),
+            latest: "2.0.0".to_owned(),
+            member: member.to_owned(),
+            kind: kind.to_owned(),
+        }
+    }
+
+    #[test]
+    fn groups_members() {
+        let groups = group_by_dependency(vec![
+            outdated("foo", "1.0.0", "b", "Normal"),
+            outdated("foo", "1.0.0", "a", "Development"),
+            outdated("foo", "1.0.0", "b", "Normal"),
+            outdated("foo", "1.1.0", "c", "Normal"),
+        ]);
+
+        assert_eq!(2, groups.len());
+        assert_eq!(
+            vec![
+                MemberUse {
+                    member: "a".to_owned(),
+                    kind: "Development".to_owned()
+                },
+                MemberUse {
+                    member: "b".to_owned(),
+                    kind: "Normal".to_owned()
+                },
+            ],
+            groups[0].used_by
+        );
+        assert_eq!("1.1.0", groups[1].project);
+        assert_eq!(1, groups[1].used_by.len());
+    }
+}
@@ -0,0 +1,217 @@
+use std::{collections::BTreeMap, fs, path::Path};
+
+use anyhow::anyhow;
+use cargo::{
+    core::SourceId,
+    util::{CargoResult, context::GlobalContext},
+};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    registry::query_versions,
+    report::{print_json_line, print_table},
+};
+
+/// A package installed with `cargo install`
+#[derive(Debug, PartialEq, Eq)]
+pub struct InstalledPackage {
+    pub name: String,
+    pub version: Version,
+    pub source_id: SourceId,
+    pub bins: Vec<String>,
+}
+
+/// An installed package with a newer version published
+#[derive(Debug, Serialize)]
+pub struct InstalledUpdate {
+    pub name: String,
+    pub installed: String,
+    pub latest: String,
+    pub bins: Vec<String>,
+}
+
+/// The `.crates2.json` tracking file of `cargo install`
+#[derive(Deserialize)]
+struct CratesV2 {
+    installs: BTreeMap<String, InstallInfo>,
+}
+
+#[derive(Deserialize)]
+struct InstallInfo {
+    #[serde(default)]
+    bins: Vec<String>,
+}
+
+/// The legacy `.crates.toml` tracking file of `cargo install`
+#[derive(Deserialize)]
+struct CratesV1 {
+    v1: BTreeMap<String, Vec<String>>,
+}
+
+/// Packages installed in `cargo_home`, according to `.crates2.json` or, for
+/// older Cargo versions, `.crates.toml`
+pub fn installed_packages(cargo_home: &Path) -> CargoResult<Vec<InstalledPackage>> {
+    let v2 = cargo_home.join(".crates2.json");
+    let v1 = cargo_home.join(".crates.toml");
+    let installs: BTreeMap<String, Vec<String>> = if v2.is_file() {
+        let crates: CratesV2 = serde_json::from_str(&fs::read_to_string(&v2)?)?;
+        crates
+            .installs
+            .into_iter()
+            .map(|(id, info)| (id, info.bins))
+            .collect()
+    } else if v1.is_file() {
+        let crates: CratesV1 = ::toml::from_str(&fs::read_to_string(&v1)?)?;
+        crates.v1
+    } else {
+        BTreeMap::new()
+    };
+
+    installs
+        .into_iter()
+        .map(|(id, bins)| {
+            let (name, version, source_id) = parse_package_id(&id)?;
+            Ok(InstalledPackage {
+                name,
+                version,
+                source_id,
+                bins,
+            })
+        })
+        .collect()
+}
+
+/// Parse a package id of the tracking files, e.g.
+/// `ripgrep 14.1.0 (registry+https://github.com/rust-lang/crates.io-index)`
+fn parse_package_id(id: &str) -> CargoResult<(String, Version, SourceId)> {
+    let invalid = || anyhow!("invalid package id `{}` in install tracking file", id);
+    let mut parts = id.splitn(3, ' ');
+    let name = parts.next().ok_or_else(invalid)?;
+    let version = Version::parse(parts.next().ok_or_else(invalid)?)?;
+    let source = parts
+        .next()
+        .and_then(|s| s.strip_prefix('('))
+        .and_then(|s| s.strip_suffix(')'))
+        .ok_or_else(invalid)?;
+    Ok((name.to_owned(), version, SourceId::from_url(source)?))
+}
+
+/// Installed registry packages with a newer version published
+pub fn installed_updates(
+    gctx: &GlobalContext,
+    packages: &[InstalledPackage],
+) -> CargoResult<Vec<InstalledUpdate>> {
+    let mut updates = vec![];
+    for package in packages.iter().filter(|p| p.source_id.is_registry()) {
+        // a registry which is no longer configured only skips its packages
+        let summaries = match query_versions(gctx, package.source_id, &package.name, false) {
+            Ok(summaries) => summaries,
+            Err(e) => {
+                gctx.shell().warn(format!(
+                    "cannot query published versions of {}: {}",
+                    package.name, e
+                ))?;
+                continue;
+            }
+        };
+        // pre-releases are only relevant to installed pre-releases
+        let pre = !package.version.pre.is_empty();
+        let latest = summaries
+            .iter()
+            .map(|s| s.version())
+            .find(|v| pre || v.pre.is_empty());
+        if let Some(latest) = latest.filter(|&latest| latest > &package.version) {
+            updates.push(InstalledUpdate {
+                name: package.name.clone(),
+                installed: package.version.to_string(),
+                latest: latest.to_string(),
+                bins: package.bins.clone(),
+            });
+        }
+    }
+    Ok(updates)
+}
+
+/// Print the installed packages with a newer version
+pub fn print_installed(updates: &[InstalledUpdate]) -> CargoResult<()> {
+    if updates.is_empty() {
+        println!("All installed packages are up to date, yay!");
+        return Ok(());
+    }
+    print_table(
+        &["Name", "Installed", "Latest", "Binaries"],
+        updates.iter().map(|update| {
+            vec![
+                update.name.clone(),
+                update.installed.clone(),
+                update.latest.clone(),
+                update.bins.join(", "),
+            ]
+        }),
+    )
+}
+
+/// Print the installed packages with a newer version as a JSON line
+pub fn print_installed_json(updates: &[InstalledUpdate]) -> CargoResult<()> {
+    print_json_line("installed", updates, None)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    const CRATES_IO: &str = "registry+https://github.com/rust-lang/crates.io-index";
+
+    #[test]
+    fn crates2_json() {
+        let home = tempfile::tempdir().unwrap();
+        fs::write(
+            home.path().join(".crates2.json"),
+            format!(
+                r#"{{"installs":{{"ripgrep 14.1.0 ({CRATES_IO})":{{"version_req":null,"bins":["rg"],"features":[],"all_features":false,"no_default_features":false,"profile":"release","target":"x86_64-unknown-linux-gnu","rustc":"rustc 1.80.0"}}}}}}"#
+            ),
+        )
+        .unwrap();
+        // ignored when .crates2.json exists
+        fs::write(home.path().join(".crates.toml"), "[v1]\n").unwrap();
+
+        assert_eq!(
+            vec![InstalledPackage {
+                name: "ripgrep".to_owned(),
+                version: Version::new(14, 1, 0),
+                source_id: SourceId::from_url(CRATES_IO).unwrap(),
+                bins: vec!["rg".to_owned()],
+            }],
+            installed_packages(home.path()).unwrap()
+        );
+    }
+
+    #[test]
+    fn crates_toml() {
+        let home = tempfile::tempdir().unwrap();
+        fs::write(
+            home.path().join(".crates.toml"),
+            format!(
+                "[v1]\n\"cargo-outdated 0.9.0 ({CRATES_IO})\" = [\"cargo-outdated\"]\n\
+                 \"local 0.1.0 (path+file:///src/local)\" = [\"local\"]\n"
+            ),
+        )
+        .unwrap();
+        let packages = installed_packages(home.path()).unwrap();
+
+        assert_eq!(2, packages.len());
+        assert_eq!("cargo-outdated", packages[0].name);
+        assert!(packages[0].source_id.is_registry());
+        assert!(packages[1].source_id.is_path());
+    }
+
+    #[test]
+    fn no_installs() {
+        let home = tempfile::tempdir().unwrap();
+        assert!(installed_packages(home.path()).unwrap().is_empty());
+        assert!(parse_package_id("ripgrep 14.1.0").is_err());
+    }
+}
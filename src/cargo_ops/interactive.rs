@@ -0,0 +1,462 @@
+use std::{
+    io::{self, BufRead, Write},
+    path::Path,
+};
+
+use cargo::util::CargoResult;
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute, terminal,
+};
+
+use super::{
+    report::{or_none, write_table},
+    upgrade::{Selection, Upgrade, manifest_changes},
+};
+
+const HELP: &str = "\
+Commands (IDS is a space separated list of numbers or names, or 'all'):
+  list, ls          show outdated dependencies and their selection
+  compat, c IDS     upgrade to the latest SemVer compatible version
+  latest, l IDS     upgrade to the latest version
+  keep, k IDS       keep the current requirement
+  diff, d           preview the manifest changes of the selection
+  apply, a          write the manifest changes and exit
+  quit, q           exit without writing anything
+  help, h           show this message
+";
+
+/// Review and select upgrades of direct dependencies, reading commands line
+/// by line from `input` so a session can be scripted. Returns whether the
+/// selection has been applied.
+pub fn run_session<R: BufRead, W: Write>(
+    upgrades: &mut [Upgrade],
+    workspace_manifest: &Path,
+    input: R,
+    out: &mut W,
+) -> CargoResult<bool> {
+    print_table(upgrades, out)?;
+    writeln!(out, "Type 'help' for a list of commands")?;
+    let mut lines = input.lines();
+    loop {
+        write!(out, "> ")?;
+        out.flush()?;
+        let Some(line) = lines.next() else {
+            writeln!(out)?;
+            return Ok(false);
+        };
+        let line = line?;
+        let mut words = line.split_ascii_whitespace();
+        let Some(command) = words.next() else {
+            continue;
+        };
+        let ids: Vec<_> = words.collect();
+        match command {
+            "list" | "ls" => print_table(upgrades, out)?,
+            "compat" | "c" => select(upgrades, &ids, Selection::Compat, out)?,
+            "latest" | "l" => select(upgrades, &ids, Selection::Latest, out)?,
+            "keep" | "k" => select(upgrades, &ids, Selection::Keep, out)?,
+            "diff" | "d" => {
+                let changes = manifest_changes(upgrades, workspace_manifest)?;
+                if changes.is_empty() {
+                    writeln!(out, "No changes selected")?;
+                }
+                for change in changes {
+                    write!(out, "{}", change.diff())?;
+                }
+            }
+            "apply" | "a" => return apply_selection(upgrades, workspace_manifest, out),
+            "quit" | "q" => return Ok(false),
+            "help" | "h" | "?" => write!(out, "{HELP}")?,
+            _ => writeln!(out, "Unknown command '{command}', type 'help' for help")?,
+        }
+    }
+}
+
+/// Write the manifest changes of the selection, returning whether there
+/// were any
+fn apply_selection<W: Write>(
+    upgrades: &[Upgrade],
+    workspace_manifest: &Path,
+    out: &mut W,
+) -> CargoResult<bool> {
+    let changes = manifest_changes(upgrades, workspace_manifest)?;
+    for change in &changes {
+        change.apply()?;
+        writeln!(out, "Updated {}", change.path.to_string_lossy())?;
+    }
+    if !changes.is_empty() {
+        writeln!(out, "Run `cargo update` to update Cargo.lock accordingly")?;
+    }
+    Ok(!changes.is_empty())
+}
+
+const KEYS: &str = "\
+up/down, j/k: move   space: toggle   c: compat   l: latest   x: keep
+d: show/hide manifest diff   a: apply   q, esc: quit";
+
+/// What a key press in the terminal UI leads to
+#[derive(Debug, PartialEq, Eq)]
+enum Step {
+    Continue,
+    Apply,
+    Quit,
+}
+
+/// State of the terminal UI, apart from the upgrades themselves
+#[derive(Default)]
+struct Tui {
+    cursor: usize,
+    /// The manifest diff of the selection, while shown
+    diff: Option<String>,
+    message: Option<String>,
+}
+
+impl Tui {
+    fn handle_key(
+        &mut self,
+        key: KeyEvent,
+        upgrades: &mut [Upgrade],
+        workspace_manifest: &Path,
+    ) -> CargoResult<Step> {
+        self.message = None;
+        if key.modifiers.contains(KeyModifiers::CONTROL) {
+            return Ok(if key.code == KeyCode::Char('c') {
+                Step::Quit
+            } else {
+                Step::Continue
+            });
+        }
+        let selection = match key.code {
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.cursor = self.cursor.saturating_sub(1);
+                return Ok(Step::Continue);
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.cursor = (self.cursor + 1).min(upgrades.len().saturating_sub(1));
+                return Ok(Step::Continue);
+            }
+            KeyCode::Char('d') => {
+                self.diff = match self.diff {
+                    Some(_) => None,
+                    None => Some(diff(upgrades, workspace_manifest)?),
+                };
+                return Ok(Step::Continue);
+            }
+            KeyCode::Char('a') => return Ok(Step::Apply),
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(Step::Quit),
+            KeyCode::Char(' ') | KeyCode::Enter => next_selection(&upgrades[self.cursor]),
+            KeyCode::Char('c') => Selection::Compat,
+            KeyCode::Char('l') => Selection::Latest,
+            KeyCode::Char('x') => Selection::Keep,
+            _ => return Ok(Step::Continue),
+        };
+        let upgrade = &mut upgrades[self.cursor];
+        if is_available(upgrade, selection) {
+            upgrade.selection = selection;
+        } else {
+            self.message = Some(format!("No {} version of {}", selection, upgrade.name));
+        }
+        // keep a shown diff in sync with the selection
+        if self.diff.is_some() {
+            self.diff = Some(diff(upgrades, workspace_manifest)?);
+        }
+        Ok(Step::Continue)
+    }
+
+    /// The screen, with `>` marking the row under the cursor
+    fn render(&self, upgrades: &[Upgrade]) -> CargoResult<String> {
+        let mut table = vec![];
+        print_table(upgrades, &mut table)?;
+        let mut screen = String::new();
+        for (i, line) in String::from_utf8(table)?.lines().enumerate() {
+            let marker = if i == self.cursor + 2 { "> " } else { "  " };
+            screen.push_str(&format!("{marker}{line}\n"));
+        }
+        screen.push_str(&format!("\n{KEYS}\n"));
+        if let Some(ref message) = self.message {
+            screen.push_str(&format!("\n{message}\n"));
+        }
+        if let Some(ref diff) = self.diff {
+            screen.push_str(&format!("\n{diff}"));
+        }
+        Ok(screen)
+    }
+}
+
+/// The selection following the current one of `upgrade`, skipping versions
+/// it doesn't have
+fn next_selection(upgrade: &Upgrade) -> Selection {
+    let order = [Selection::Keep, Selection::Compat, Selection::Latest];
+    let current = order
+        .iter()
+        .position(|&s| s == upgrade.selection)
+        .unwrap_or(0);
+    (1..=order.len())
+        .map(|offset| order[(current + offset) % order.len()])
+        .find(|&selection| is_available(upgrade, selection))
+        .unwrap_or(Selection::Keep)
+}
+
+fn is_available(upgrade: &Upgrade, selection: Selection) -> bool {
+    match selection {
+        Selection::Keep => true,
+        Selection::Compat => upgrade.compat.is_some(),
+        Selection::Latest => upgrade.latest.is_some(),
+    }
+}
+
+/// The manifest diff of the selection
+fn diff(upgrades: &[Upgrade], workspace_manifest: &Path) -> CargoResult<String> {
+    let changes = manifest_changes(upgrades, workspace_manifest)?;
+    if changes.is_empty() {
+        return Ok("No changes selected\n".to_owned());
+    }
+    Ok(changes.iter().map(|change| change.diff()).collect())
+}
+
+/// Raw mode and the alternate screen of the terminal, left on drop
+struct RawTerminal;
+
+impl RawTerminal {
+    fn enter() -> CargoResult<RawTerminal> {
+        terminal::enable_raw_mode()?;
+        let terminal = RawTerminal;
+        execute!(io::stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+        Ok(terminal)
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Review and select upgrades of direct dependencies in a terminal UI,
+/// toggling each dependency between its current requirement and the compat
+/// or latest version. Returns whether the selection has been applied.
+pub fn run_tui(upgrades: &mut [Upgrade], workspace_manifest: &Path) -> CargoResult<bool> {
+    let mut tui = Tui::default();
+    let step = {
+        let _terminal = RawTerminal::enter()?;
+        let mut stdout = io::stdout();
+        loop {
+            execute!(
+                stdout,
+                cursor::MoveTo(0, 0),
+                terminal::Clear(terminal::ClearType::All)
+            )?;
+            // raw mode doesn't return the carriage on line feeds
+            write!(stdout, "{}", tui.render(upgrades)?.replace('\n', "\r\n"))?;
+            stdout.flush()?;
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match tui.handle_key(key, upgrades, workspace_manifest)? {
+                Step::Continue => {}
+                step => break step,
+            }
+        }
+    };
+    match step {
+        Step::Apply => apply_selection(upgrades, workspace_manifest, &mut io::stdout()),
+        _ => Ok(false),
+    }
+}
+
+fn print_table<W: Write>(upgrades: &[Upgrade], out: &mut W) -> CargoResult<()> {
+    write_table(
+        out,
+        &[
+            "#", "Name", "Member", "Project", "Compat", "Latest", "Kind", "Selected",
+        ],
+        upgrades.iter().enumerate().map(|(i, upgrade)| {
+            vec![
+                (i + 1).to_string(),
+                upgrade.name.clone(),
+                upgrade.member.clone(),
+                upgrade.project.to_string(),
+                or_none(upgrade.compat.as_ref()),
+                or_none(upgrade.latest.as_ref()),
+                format!("{:?}", upgrade.kind),
+                upgrade.selection.to_string(),
+            ]
+        }),
+    )
+}
+
+fn select<W: Write>(
+    upgrades: &mut [Upgrade],
+    ids: &[&str],
+    selection: Selection,
+    out: &mut W,
+) -> CargoResult<()> {
+    if ids.is_empty() {
+        writeln!(out, "No dependencies given")?;
+        return Ok(());
+    }
+    for &id in ids {
+        let indices: Vec<usize> = match id.parse::<usize>() {
+            _ if id == "all" => (0..upgrades.len()).collect(),
+            Ok(n) if n >= 1 && n <= upgrades.len() => vec![n - 1],
+            Ok(_) => vec![],
+            Err(_) => (0..upgrades.len())
+                .filter(|&i| upgrades[i].name == id)
+                .collect(),
+        };
+        if indices.is_empty() {
+            writeln!(out, "No outdated dependency '{id}'")?;
+        }
+        for i in indices {
+            let upgrade = &mut upgrades[i];
+            if is_available(upgrade, selection) {
+                upgrade.selection = selection;
+            } else if id != "all" {
+                writeln!(out, "No {} version of {}", selection, upgrade.name)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::{fs, io::Cursor, path::PathBuf};
+
+    use cargo::core::dependency::DepKind;
+    use semver::Version;
+
+    use super::*;
+
+    fn fixture() -> (tempfile::TempDir, PathBuf, Vec<Upgrade>) {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest = dir.path().join("Cargo.toml");
+        fs::write(
+            &manifest,
+            "[package]\nname = \"a\"\nversion = \"0.1.0\"\n\n[dependencies]\nlog = \"0.4.8\"\n\
+             rand = \"0.7\"\n",
+        )
+        .unwrap();
+        let upgrade = |name: &str, compat: Option<Version>| Upgrade {
+            member: "a".to_owned(),
+            manifest_path: manifest.clone(),
+            name: name.to_owned(),
+            package: name.to_owned(),
+            kind: DepKind::Normal,
+            platform: None,
+            project: Version::new(0, 4, 8),
+            compat,
+            latest: Some(Version::new(0, 10, 3)),
+            selection: Selection::Keep,
+        };
+        let upgrades = vec![
+            upgrade("log", Some(Version::new(0, 4, 34))),
+            upgrade("rand", None),
+        ];
+        (dir, manifest, upgrades)
+    }
+
+    fn session(script: &str) -> (bool, String, String) {
+        let (_dir, manifest, mut upgrades) = fixture();
+        let mut out = vec![];
+        let applied = run_session(&mut upgrades, &manifest, Cursor::new(script), &mut out).unwrap();
+        (
+            applied,
+            String::from_utf8(out).unwrap(),
+            fs::read_to_string(&manifest).unwrap(),
+        )
+    }
+
+    #[test]
+    fn apply() {
+        let (applied, out, manifest) = session("compat log\nlatest 2\ndiff\napply\n");
+        assert!(applied);
+        assert!(out.contains("@@ line 7 @@\n-rand = \"0.7\"\n+rand = \"0.10.3\"\n"));
+        assert!(manifest.contains("log = \"0.4.34\"\n"));
+        assert!(manifest.contains("rand = \"0.10.3\"\n"));
+    }
+
+    #[test]
+    fn quit() {
+        let (applied, _, manifest) = session("latest all\nquit\napply\n");
+        assert!(!applied);
+        assert!(manifest.contains("log = \"0.4.8\"\n"));
+    }
+
+    #[test]
+    fn unavailable() {
+        let (applied, out, manifest) = session("compat rand 3\nkeep all\napply");
+        assert!(!applied);
+        assert!(out.contains("No compat version of rand\n"));
+        assert!(out.contains("No outdated dependency '3'\n"));
+        assert!(manifest.contains("rand = \"0.7\"\n"));
+    }
+
+    fn press(tui: &mut Tui, code: KeyCode, upgrades: &mut [Upgrade], manifest: &Path) -> Step {
+        tui.handle_key(KeyEvent::from(code), upgrades, manifest)
+            .unwrap()
+    }
+
+    #[test]
+    fn tui_toggle() {
+        let (_dir, manifest, mut upgrades) = fixture();
+        let mut tui = Tui::default();
+        let mut selections = vec![];
+        for _ in 0..3 {
+            press(&mut tui, KeyCode::Char(' '), &mut upgrades, &manifest);
+            selections.push(upgrades[0].selection);
+        }
+        assert_eq!(
+            vec![Selection::Compat, Selection::Latest, Selection::Keep],
+            selections
+        );
+
+        press(&mut tui, KeyCode::Down, &mut upgrades, &manifest);
+        press(&mut tui, KeyCode::Char('c'), &mut upgrades, &manifest);
+        assert_eq!(Selection::Keep, upgrades[1].selection);
+        assert!(
+            tui.render(&upgrades)
+                .unwrap()
+                .contains("No compat version of rand\n")
+        );
+
+        // rand has no compat version to toggle to
+        press(&mut tui, KeyCode::Char(' '), &mut upgrades, &manifest);
+        press(&mut tui, KeyCode::Char('d'), &mut upgrades, &manifest);
+        let screen = tui.render(&upgrades).unwrap();
+        assert!(screen.contains("> 2 "));
+        assert!(screen.contains("+rand = \"0.10.3\"\n"));
+        assert_eq!(
+            Step::Apply,
+            press(&mut tui, KeyCode::Char('a'), &mut upgrades, &manifest)
+        );
+        assert!(apply_selection(&upgrades, &manifest, &mut vec![]).unwrap());
+        assert!(
+            fs::read_to_string(&manifest)
+                .unwrap()
+                .contains("rand = \"0.10.3\"\n")
+        );
+    }
+
+    #[test]
+    fn tui_quit() {
+        let (_dir, manifest, mut upgrades) = fixture();
+        let mut tui = Tui::default();
+        let ctrl_c = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
+        assert_eq!(
+            Step::Quit,
+            tui.handle_key(ctrl_c, &mut upgrades, &manifest).unwrap()
+        );
+        assert_eq!(Selection::Keep, upgrades[0].selection);
+        assert_eq!(
+            Step::Quit,
+            press(&mut tui, KeyCode::Esc, &mut upgrades, &manifest)
+        );
+    }
+}
@@ -0,0 +1,154 @@
+use std::collections::BTreeMap;
+
+use cargo::util::CargoResult;
+use serde::Serialize;
+
+use super::report::{print_json, print_table};
+
+/// A direct dependency pinned to the lowest version its requirement allows
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct MinimalPin {
+    pub member: String,
+    pub name: String,
+    pub requirement: String,
+    pub lowest: String,
+}
+
+/// A direct dependency whose lowest version does not resolve
+#[derive(Serialize)]
+struct UnresolvablePin<'a> {
+    #[serde(flatten)]
+    pin: &'a MinimalPin,
+    conflicts_with: &'a str,
+}
+
+/// A JSON line describing the outcome of `--minimal`
+#[derive(Serialize)]
+struct MinimalMetadata<'a> {
+    unresolvable: Vec<UnresolvablePin<'a>>,
+    transitive_error: Option<&'a str>,
+}
+
+/// The outcome of `--minimal`
+pub struct MinimalReport {
+    /// Direct dependencies pinned to their lowest versions
+    pub pins: Vec<MinimalPin>,
+    /// Dependencies whose lowest version does not resolve, along with what
+    /// they conflict with
+    pub unresolvable: BTreeMap<String, String>,
+    /// Why the dependencies do not resolve when transitive dependencies are
+    /// at their minimal versions too, if they do not
+    pub transitive_error: Option<String>,
+}
+
+impl MinimalReport {
+    /// Number of issues found
+    pub fn count(&self) -> i32 {
+        self.unresolvable_pins().count() as i32 + self.transitive_error.is_some() as i32
+    }
+
+    fn unresolvable_pins(&self) -> impl Iterator<Item = (&MinimalPin, &str)> {
+        self.pins.iter().filter_map(|pin| {
+            self.unresolvable
+                .get(&pin.name)
+                .map(|conflict| (pin, conflict.as_str()))
+        })
+    }
+
+    /// Rows of the table of unresolvable lower bounds
+    fn rows(&self) -> Vec<Vec<String>> {
+        self.unresolvable_pins()
+            .map(|(pin, conflict)| {
+                vec![
+                    pin.name.clone(),
+                    pin.member.clone(),
+                    pin.requirement.clone(),
+                    pin.lowest.clone(),
+                    conflict.to_owned(),
+                ]
+            })
+            .collect()
+    }
+
+    pub fn print_list(&self) -> CargoResult<()> {
+        if self.count() == 0 {
+            println!("All lower bounds resolve, yay!");
+            return Ok(());
+        }
+        print_table(
+            &["Name", "Member", "Requirement", "Lowest", "Conflicts with"],
+            self.rows(),
+        )?;
+        if let Some(ref error) = self.transitive_error {
+            println!("Transitive dependencies do not resolve at their minimal versions: {error}");
+        }
+        Ok(())
+    }
+
+    pub fn print_json(&self, workspace_root: Option<&str>) -> CargoResult<()> {
+        let unresolvable = self
+            .unresolvable_pins()
+            .map(|(pin, conflicts_with)| UnresolvablePin {
+                pin,
+                conflicts_with,
+            })
+            .collect();
+        print_json(
+            &MinimalMetadata {
+                unresolvable,
+                transitive_error: self.transitive_error.as_deref(),
+            },
+            workspace_root,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    fn pin(member: &str, name: &str, requirement: &str, lowest: &str) -> MinimalPin {
+        MinimalPin {
+            member: member.to_owned(),
+            name: name.to_owned(),
+            requirement: requirement.to_owned(),
+            lowest: lowest.to_owned(),
+        }
+    }
+
+    #[test]
+    fn unresolvable_rows() {
+        let report = MinimalReport {
+            pins: vec![
+                pin("app", "log", "0.4", "0.4.0"),
+                pin("app", "rand", "0.8", "0.8.0"),
+                pin("lib", "rand", "0.8.1", "0.8.1"),
+            ],
+            unresolvable: BTreeMap::from([("rand".to_owned(), "log".to_owned())]),
+            transitive_error: None,
+        };
+
+        assert_eq!(2, report.count());
+        assert_eq!(
+            vec![
+                vec!["rand", "app", "0.8", "0.8.0", "log"],
+                vec!["rand", "lib", "0.8.1", "0.8.1", "log"],
+            ],
+            report.rows()
+        );
+    }
+
+    #[test]
+    fn transitive_error() {
+        let report = MinimalReport {
+            pins: vec![pin("app", "log", "0.4", "0.4.0")],
+            unresolvable: BTreeMap::new(),
+            transitive_error: Some("failed to select a version".to_owned()),
+        };
+
+        assert_eq!(1, report.count());
+        assert!(report.rows().is_empty());
+    }
+}
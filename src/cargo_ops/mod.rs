@@ -1,10 +1,49 @@
 use super::Options;
 use toml::value::{Table, Value};
 
+mod changelog;
+mod consistency;
+mod delta;
+mod discover;
 mod elaborate_workspace;
+mod features;
+mod grouped;
+mod installed;
+mod interactive;
+mod minimal;
+mod patches;
+mod path_deps;
 mod pkg_status;
+mod plan;
+mod registry;
+mod report;
+mod requirements;
+mod scratch;
+mod supply_chain;
 mod temp_project;
-pub use self::{elaborate_workspace::ElaborateWorkspace, temp_project::TempProject};
+mod update_script;
+mod upgrade;
+mod verify;
+pub use self::{
+    consistency::{print_shared_dependencies, print_shared_dependencies_json, shared_dependencies},
+    delta::PackageDelta,
+    discover::{WorkspaceRoots, workspace_roots},
+    elaborate_workspace::ElaborateWorkspace,
+    grouped::{group_by_dependency, print_groups, print_groups_json},
+    installed::{installed_packages, installed_updates, print_installed, print_installed_json},
+    interactive::{run_session, run_tui},
+    minimal::MinimalReport,
+    patches::{patch_statuses, print_patches, print_patches_json},
+    path_deps::{path_dependencies, print_path_dependencies, print_path_dependencies_json},
+    plan::{print_plan, print_plan_json, upgrade_plan},
+    report::print_json,
+    requirements::{print_requirements, print_requirements_json},
+    scratch::scratch_project,
+    supply_chain::{print_supply_chain, print_supply_chain_json, supply_chain_changes},
+    temp_project::{TempProject, resolves_with},
+    update_script::{PreciseUpdate, print_update_script, print_update_script_json},
+    verify::{print_verifications, print_verifications_json, verify_batches},
+};
 
 /// A continent struct for quick parsing and manipulating manifest
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
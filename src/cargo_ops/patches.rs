@@ -0,0 +1,143 @@
+use cargo::{core::SourceId, util::CargoResult};
+use semver::Version;
+use serde::Serialize;
+
+use super::{
+    ElaborateWorkspace,
+    registry::query_versions,
+    report::{or_none, print_json_line, print_section},
+};
+
+/// The status of a `[patch]` entry of the workspace
+#[derive(Serialize)]
+pub struct PatchStatus {
+    pub name: String,
+    /// The URL of the patched source
+    pub source: String,
+    /// Version of the patch, if it could be found
+    pub patched: Option<String>,
+    /// Latest version published by the patched source, if it is a registry
+    pub upstream: Option<String>,
+    /// Whether the resolve uses the patch
+    pub used: bool,
+    /// Whether the upstream version is at least the patched one, so the
+    /// patch can likely be dropped
+    pub droppable: bool,
+}
+
+/// Statuses of the `[patch]` entries of the workspace of `ela`
+pub fn patch_statuses(ela: &ElaborateWorkspace<'_>) -> CargoResult<Vec<PatchStatus>> {
+    let gctx = ela.workspace.gctx();
+    let crates_io = SourceId::crates_io(gctx)?;
+    let mut statuses = vec![];
+    for (url, deps) in ela.workspace.root_patch()? {
+        // only registries can be compared with, a git source has no versions
+        let registry = if url == *crates_io.url() {
+            Some(SourceId::crates_io_maybe_sparse_http(gctx)?)
+        } else {
+            ela.pkgs
+                .keys()
+                .map(|id| id.source_id())
+                .find(|id| id.is_registry() && *id.url() == url)
+        };
+        for dep in deps {
+            let used = ela.pkgs.keys().find(|&&id| dep.matches_id(id));
+            let patch = used.or_else(|| ela.unused_patches.iter().find(|&&id| dep.matches_id(id)));
+            let patched = patch.map(|id| id.version().clone());
+            let upstream = match registry {
+                Some(registry) => {
+                    let name = dep.package_name();
+                    match query_versions(gctx, registry, &name, false) {
+                        Ok(summaries) => {
+                            latest_upstream(summaries.iter().map(|s| s.version()), patched.as_ref())
+                        }
+                        Err(e) => {
+                            gctx.shell()
+                                .warn(format!("cannot query upstream versions of {name}: {e}"))?;
+                            None
+                        }
+                    }
+                }
+                None => None,
+            };
+            statuses.push(PatchStatus {
+                name: dep.package_name().to_string(),
+                source: url.to_string(),
+                droppable: matches!((&upstream, &patched), (Some(u), Some(p)) if u >= p),
+                patched: patched.map(|v| v.to_string()),
+                upstream: upstream.map(|v| v.to_string()),
+                used: used.is_some(),
+            });
+        }
+    }
+    statuses.sort_by(|a, b| (&a.source, &a.name).cmp(&(&b.source, &b.name)));
+    Ok(statuses)
+}
+
+/// The newest of `versions`, pre-releases only being considered when the
+/// patch is one itself
+fn latest_upstream<'v>(
+    versions: impl Iterator<Item = &'v Version>,
+    patched: Option<&Version>,
+) -> Option<Version> {
+    let pre = patched.is_some_and(|p| !p.pre.is_empty());
+    versions.filter(|v| pre || v.pre.is_empty()).max().cloned()
+}
+
+/// Print the `[patch]` section of the report, if the workspace has patches
+pub fn print_patches(statuses: &[PatchStatus]) -> CargoResult<()> {
+    if statuses.is_empty() {
+        return Ok(());
+    }
+    let yes_no = |b: bool| if b { "Yes" } else { "No" }.to_owned();
+    print_section(
+        "Patches",
+        &["Name", "Patched", "Upstream", "Used", "Droppable", "Source"],
+        statuses.iter().map(|status| {
+            vec![
+                status.name.clone(),
+                or_none(status.patched.as_ref()),
+                or_none(status.upstream.as_ref()),
+                yes_no(status.used),
+                yes_no(status.droppable),
+                status.source.clone(),
+            ]
+        }),
+    )
+}
+
+/// Print the `[patch]` entries as a JSON line, if the workspace has patches
+pub fn print_patches_json(
+    statuses: &[PatchStatus],
+    workspace_root: Option<&str>,
+) -> CargoResult<()> {
+    if !statuses.is_empty() {
+        print_json_line("patches", statuses, workspace_root)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn upstream() {
+        let versions = [
+            Version::parse("1.0.0").unwrap(),
+            Version::parse("1.2.0-beta.1").unwrap(),
+            Version::parse("1.1.0").unwrap(),
+        ];
+        let stable = Version::parse("1.0.5").unwrap();
+        let pre = Version::parse("1.2.0-alpha.1").unwrap();
+        assert_eq!(
+            Some(Version::new(1, 1, 0)),
+            latest_upstream(versions.iter(), Some(&stable))
+        );
+        assert_eq!(
+            Some(Version::parse("1.2.0-beta.1").unwrap()),
+            latest_upstream(versions.iter(), Some(&pre))
+        );
+        assert_eq!(None, latest_upstream([].iter(), Some(&stable)));
+    }
+}
@@ -0,0 +1,151 @@
+use std::collections::BTreeMap;
+
+use cargo::{
+    core::{Dependency, SourceId},
+    util::CargoResult,
+};
+use semver::Version;
+use serde::Serialize;
+
+use super::{
+    ElaborateWorkspace,
+    registry::query_versions,
+    report::{print_json_line, print_section},
+};
+
+/// A path dependency along with the latest version of the package of the
+/// same name published on its registry
+#[derive(Serialize)]
+pub struct PathDependency {
+    pub name: String,
+    /// Version of the local path crate
+    pub local: String,
+    /// Latest version published on the registry
+    pub registry: String,
+    pub path: String,
+}
+
+/// Path dependencies of the workspace of `ela`, other than its members,
+/// which are also published on the registry they would be published to
+pub fn path_dependencies(ela: &ElaborateWorkspace<'_>) -> CargoResult<Vec<PathDependency>> {
+    let gctx = ela.workspace.gctx();
+    let crates_io = SourceId::crates_io_maybe_sparse_http(gctx)?;
+    // the registry of each path package, by its declarations
+    let mut registries = BTreeMap::new();
+    for deps in ela.pkg_deps.values() {
+        for (&id, dep) in deps {
+            if id.source_id().is_path() && !ela.workspace.members().any(|m| m.package_id() == id) {
+                registries.insert(id, registry_of(dep, crates_io));
+            }
+        }
+    }
+
+    let mut published = vec![];
+    for (id, registry) in registries {
+        let summaries = match query_versions(gctx, registry, id.name().as_str(), false) {
+            Ok(summaries) => summaries,
+            Err(e) => {
+                gctx.shell().warn(format!(
+                    "cannot query published versions of {}: {}",
+                    id.name(),
+                    e
+                ))?;
+                continue;
+            }
+        };
+        if let Some(latest) = latest_published(summaries.iter().map(|s| s.version()), id.version())
+        {
+            published.push(PathDependency {
+                name: id.name().to_string(),
+                local: id.version().to_string(),
+                registry: latest.to_string(),
+                path: ela.pkgs[&id].root().to_string_lossy().into_owned(),
+            });
+        }
+    }
+    Ok(published)
+}
+
+/// The registry a path dependency would be published to, the one declared
+/// by `dep` or crates.io
+fn registry_of(dep: &Dependency, crates_io: SourceId) -> SourceId {
+    dep.registry_id().unwrap_or(crates_io)
+}
+
+/// The newest of `versions`, pre-releases only being considered when the
+/// `local` version is one itself
+fn latest_published<'v>(
+    versions: impl Iterator<Item = &'v Version>,
+    local: &Version,
+) -> Option<&'v Version> {
+    let pre = !local.pre.is_empty();
+    versions.filter(|v| pre || v.pre.is_empty()).max()
+}
+
+/// Print the path dependencies section of the report, if any is published
+pub fn print_path_dependencies(deps: &[PathDependency]) -> CargoResult<()> {
+    if deps.is_empty() {
+        return Ok(());
+    }
+    print_section(
+        "Path dependencies",
+        &["Name", "Local", "Registry", "Path"],
+        deps.iter().map(|dep| {
+            vec![
+                dep.name.clone(),
+                dep.local.clone(),
+                dep.registry.clone(),
+                dep.path.clone(),
+            ]
+        }),
+    )
+}
+
+/// Print the published path dependencies as a JSON line, if any
+pub fn print_path_dependencies_json(
+    deps: &[PathDependency],
+    workspace_root: Option<&str>,
+) -> CargoResult<()> {
+    if !deps.is_empty() {
+        print_json_line("path_dependencies", deps, workspace_root)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn registry() {
+        let crates_io =
+            SourceId::from_url("registry+https://github.com/rust-lang/crates.io-index").unwrap();
+        let company = SourceId::from_url("sparse+https://crates.example.com/index/").unwrap();
+        let path = SourceId::from_url("path+file:///src/fork").unwrap();
+        let mut dep = Dependency::parse("fork", None, path).unwrap();
+        assert_eq!(crates_io, registry_of(&dep, crates_io));
+
+        dep.set_registry_id(company);
+        assert_eq!(company, registry_of(&dep, crates_io));
+    }
+
+    #[test]
+    fn pre_releases() {
+        let versions = [
+            Version::parse("0.9.0").unwrap(),
+            Version::parse("1.0.0-rc.1").unwrap(),
+            Version::parse("0.8.2").unwrap(),
+        ];
+        let latest = |local: &str| {
+            latest_published(versions.iter(), &Version::parse(local).unwrap()).cloned()
+        };
+        assert_eq!(Some(Version::new(0, 9, 0)), latest("0.8.0"));
+        assert_eq!(
+            Some(Version::parse("1.0.0-rc.1").unwrap()),
+            latest("1.0.0-alpha")
+        );
+        assert_eq!(None, latest_published([].iter(), &Version::new(0, 8, 0)));
+    }
+}
@@ -2,12 +2,17 @@ use std::fmt;
 
 use semver::Version;
 
+use super::changelog::ReleaseNotes;
+
 /// Enum which represents the update status of a package
 #[derive(Debug)]
 pub enum Status {
     Unchanged,
     Removed,
     Version(Version),
+    /// Upgrading makes the dependencies unresolvable, because of a conflict
+    /// with the given package
+    Unresolvable(String),
 }
 
 impl Status {
@@ -24,6 +29,14 @@ impl Status {
     }
 
     pub fn is_changed(&self) -> bool { !matches!(*self, Status::Unchanged) }
+
+    /// The new version, if any
+    pub fn version(&self) -> Option<&Version> {
+        match self {
+            Status::Version(version) => Some(version),
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Display for Status {
@@ -32,6 +45,7 @@ impl fmt::Display for Status {
             Status::Unchanged => write!(f, "---"),
             Status::Removed => write!(f, "Removed"),
             Status::Version(version) => version.fmt(f),
+            Status::Unresolvable(_) => write!(f, "Unresolvable"),
         }
     }
 }
@@ -40,4 +54,13 @@ impl fmt::Display for Status {
 pub struct PkgStatus {
     pub compat: Status,
     pub latest: Status,
+    /// Release notes of the upgraded version, only gathered for direct
+    /// dependencies with `--changelog`
+    pub release_notes: Option<ReleaseNotes>,
+    /// Why the package is missing from the compatible resolve, only gathered
+    /// with `--explain`
+    pub compat_removal: Option<String>,
+    /// Why the package is missing from the latest resolve, only gathered
+    /// with `--explain`
+    pub latest_removal: Option<String>,
 }
@@ -0,0 +1,356 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use cargo::{
+    core::{Dependency, PackageId, dependency::DepKind},
+    util::{CargoResult, OptVersionReq},
+};
+use semver::Version;
+use serde::Serialize;
+
+use super::{
+    ElaborateWorkspace,
+    report::{or_none, print_json_line, print_section},
+    upgrade::Upgrade,
+};
+
+/// The upgrade of a direct dependency, across the members declaring it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PlannedUpgrade {
+    pub name: String,
+    /// Oldest version currently used by the members
+    pub project: Version,
+    pub target: Version,
+    /// Whether the target is outside the requirements of the members
+    pub breaking: bool,
+    pub members: BTreeSet<String>,
+    /// Other planned upgrades whose current version the target version does
+    /// not accept
+    pub requires: BTreeSet<String>,
+}
+
+/// Upgrades which must move together
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct Batch {
+    pub breaking: bool,
+    pub upgrades: Vec<PlannedUpgrade>,
+}
+
+/// Plan the `upgrades` of direct dependencies of the workspace members, to
+/// their latest versions when outdated or compatible versions otherwise,
+/// coupling those whose target version, as found in the latest workspace
+/// `ela_latest` or else the compatible one `ela_compat`, requires a version of
+/// another which the project does not use
+pub fn upgrade_plan(
+    ela_compat: &ElaborateWorkspace<'_>,
+    ela_latest: &ElaborateWorkspace<'_>,
+    upgrades: &[Upgrade],
+) -> Vec<Batch> {
+    plan_upgrades(upgrades, |name, target| {
+        // the target is the compatible version when the latest one is
+        // unresolvable, so it is only found in the compatible workspace
+        let packages = ela_latest
+            .pkgs
+            .iter()
+            .chain(&ela_compat.pkgs)
+            .map(|(&id, pkg)| (id, pkg.dependencies()));
+        target_requirements(packages, name, target)
+    })
+}
+
+/// Requirements declared by the first of `packages` named `name` at version
+/// `target` on its normal and build dependencies
+fn target_requirements<'a>(
+    packages: impl IntoIterator<Item = (PackageId, &'a [Dependency])>,
+    name: &str,
+    target: &Version,
+) -> Vec<(String, OptVersionReq)> {
+    packages
+        .into_iter()
+        .find(|(id, _)| id.name().as_str() == name && id.version() == target)
+        .map(|(_, dependencies)| {
+            dependencies
+                .iter()
+                .filter(|dep| dep.kind() != DepKind::Development)
+                .map(|dep| (dep.package_name().to_string(), dep.version_req().clone()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Plan the `upgrades`, given the requirements declared by a package at a
+/// given version on its dependencies
+fn plan_upgrades<F>(upgrades: &[Upgrade], requirements: F) -> Vec<Batch>
+where
+    F: Fn(&str, &Version) -> Vec<(String, OptVersionReq)>,
+{
+    let mut planned: BTreeMap<String, PlannedUpgrade> = BTreeMap::new();
+    for upgrade in upgrades {
+        let Some(target) = upgrade.latest.as_ref().or(upgrade.compat.as_ref()) else {
+            continue;
+        };
+        let entry = planned
+            .entry(upgrade.package.clone())
+            .or_insert_with(|| PlannedUpgrade {
+                name: upgrade.package.clone(),
+                project: upgrade.project.clone(),
+                target: target.clone(),
+                breaking: false,
+                members: BTreeSet::new(),
+                requires: BTreeSet::new(),
+            });
+        entry.project = entry.project.clone().min(upgrade.project.clone());
+        entry.target = entry.target.clone().max(target.clone());
+        entry.breaking |= upgrade.compat.as_ref() != Some(target);
+        entry.members.insert(upgrade.member.clone());
+    }
+
+    let projects: BTreeMap<_, _> = planned
+        .values()
+        .map(|p| (p.name.clone(), p.project.clone()))
+        .collect();
+    for upgrade in planned.values_mut() {
+        for (name, req) in requirements(&upgrade.name, &upgrade.target) {
+            if name == upgrade.name {
+                continue;
+            }
+            if let Some(project) = projects.get(&name)
+                && !req.matches(project)
+            {
+                upgrade.requires.insert(name);
+            }
+        }
+    }
+    plan_batches(planned.into_values().collect())
+}
+
+/// Group `upgrades` coupled by their requirements into batches, ordered from
+/// the least to the most disruptive
+fn plan_batches(upgrades: Vec<PlannedUpgrade>) -> Vec<Batch> {
+    // union find over the coupled upgrades
+    let index: BTreeMap<_, _> = upgrades
+        .iter()
+        .enumerate()
+        .map(|(i, u)| (u.name.clone(), i))
+        .collect();
+    let mut parents: Vec<_> = (0..upgrades.len()).collect();
+    fn find(parents: &mut [usize], i: usize) -> usize {
+        if parents[i] != i {
+            parents[i] = find(parents, parents[i]);
+        }
+        parents[i]
+    }
+    for (i, upgrade) in upgrades.iter().enumerate() {
+        for required in &upgrade.requires {
+            let (a, b) = (find(&mut parents, i), find(&mut parents, index[required]));
+            parents[a] = b;
+        }
+    }
+
+    let mut batches: BTreeMap<usize, Vec<PlannedUpgrade>> = BTreeMap::new();
+    for (i, upgrade) in upgrades.into_iter().enumerate() {
+        let root = find(&mut parents, i);
+        batches.entry(root).or_default().push(upgrade);
+    }
+    let mut batches: Vec<_> = batches
+        .into_values()
+        .map(|upgrades| Batch {
+            breaking: upgrades.iter().any(|u| u.breaking),
+            upgrades,
+        })
+        .collect();
+    batches.sort_by(|a, b| {
+        (a.breaking, a.upgrades.len(), &a.upgrades[0].name).cmp(&(
+            b.breaking,
+            b.upgrades.len(),
+            &b.upgrades[0].name,
+        ))
+    });
+    batches
+}
+
+/// Print the upgrade plan section of the report, if anything is outdated
+pub fn print_plan(batches: &[Batch]) -> CargoResult<()> {
+    if batches.is_empty() {
+        return Ok(());
+    }
+    let join = |names: &BTreeSet<String>| {
+        let names: Vec<_> = names.iter().map(String::as_str).collect();
+        names.join(", ")
+    };
+    print_section(
+        "Upgrade plan",
+        &["Batch", "Name", "Project", "Target", "Members", "Requires"],
+        batches.iter().enumerate().flat_map(|(i, batch)| {
+            batch.upgrades.iter().map(move |upgrade| {
+                vec![
+                    format!(
+                        "{}{}",
+                        i + 1,
+                        if batch.breaking { " (breaking)" } else { "" }
+                    ),
+                    upgrade.name.clone(),
+                    upgrade.project.to_string(),
+                    upgrade.target.to_string(),
+                    join(&upgrade.members),
+                    or_none(Some(join(&upgrade.requires)).filter(|r| !r.is_empty())),
+                ]
+            })
+        }),
+    )
+}
+
+/// Print the upgrade plan as a JSON line, if anything is outdated
+pub fn print_plan_json(batches: &[Batch], workspace_root: Option<&str>) -> CargoResult<()> {
+    if !batches.is_empty() {
+        print_json_line("upgrade_plan", batches, workspace_root)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::path::PathBuf;
+
+    use cargo::core::SourceId;
+    use pretty_assertions::assert_eq;
+    use semver::VersionReq;
+
+    use crate::cargo_ops::upgrade::Selection;
+
+    fn upgrade(package: &str, project: &str, latest: &str) -> Upgrade {
+        Upgrade {
+            member: "app".to_owned(),
+            manifest_path: PathBuf::from("Cargo.toml"),
+            name: package.to_owned(),
+            package: package.to_owned(),
+            kind: DepKind::Normal,
+            platform: None,
+            project: Version::parse(project).unwrap(),
+            compat: None,
+            latest: Some(Version::parse(latest).unwrap()),
+            selection: Selection::Keep,
+        }
+    }
+
+    fn requirements(name: &str, _target: &Version) -> Vec<(String, OptVersionReq)> {
+        let req = |name: &str, req: &str| {
+            (
+                name.to_owned(),
+                OptVersionReq::Req(VersionReq::parse(req).unwrap()),
+            )
+        };
+        match name {
+            "hyper" => vec![req("tokio", "1"), req("http", "1")],
+            "tower" => vec![req("tokio", "0.2")],
+            "rand" => vec![req("rand_core", "0.6")],
+            _ => vec![],
+        }
+    }
+
+    fn names(batches: &[Batch]) -> Vec<Vec<&str>> {
+        batches
+            .iter()
+            .map(|b| b.upgrades.iter().map(|u| u.name.as_str()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn coupled_upgrades() {
+        let batches = plan_upgrades(
+            &[
+                upgrade("http", "0.2.12", "1.1.0"),
+                upgrade("hyper", "0.14.30", "1.4.1"),
+                upgrade("tokio", "0.2.25", "1.40.0"),
+            ],
+            requirements,
+        );
+
+        assert_eq!(vec![vec!["http", "hyper", "tokio"]], names(&batches));
+        assert_eq!(
+            BTreeSet::from(["http".to_owned(), "tokio".to_owned()]),
+            batches[0].upgrades[1].requires
+        );
+    }
+
+    #[test]
+    fn independent_upgrades() {
+        // tower still accepts the current tokio, and rand_core is not upgraded
+        let batches = plan_upgrades(
+            &[
+                upgrade("rand", "0.7.3", "0.8.5"),
+                upgrade("tokio", "0.2.25", "1.40.0"),
+                upgrade("tower", "0.3.1", "0.4.13"),
+            ],
+            requirements,
+        );
+
+        assert_eq!(
+            vec![vec!["rand"], vec!["tokio"], vec!["tower"]],
+            names(&batches)
+        );
+        assert!(batches.iter().all(|b| b.upgrades[0].requires.is_empty()));
+    }
+
+    #[test]
+    fn compat_target() {
+        // the latest hyper is unresolvable, so its compatible version is only
+        // found among the compatible packages
+        let source =
+            SourceId::from_url("registry+https://github.com/rust-lang/crates.io-index").unwrap();
+        let package =
+            |name: &str, version: &str| PackageId::try_new(name, version, source).unwrap();
+        let hyper = [Dependency::parse("tokio", Some("1"), source).unwrap()];
+        let latest = [(package("tokio", "1.40.0"), &[][..])];
+        let compat = [(package("hyper", "0.14.30"), &hyper[..])];
+        let hyper = Upgrade {
+            compat: Some(Version::new(0, 14, 30)),
+            latest: None,
+            ..upgrade("hyper", "0.14.10", "0.14.30")
+        };
+
+        let batches = plan_upgrades(
+            &[hyper, upgrade("tokio", "0.2.25", "1.40.0")],
+            |name, target| target_requirements(latest.iter().chain(&compat).copied(), name, target),
+        );
+
+        assert_eq!(vec![vec!["hyper", "tokio"]], names(&batches));
+        assert_eq!(
+            BTreeSet::from(["tokio".to_owned()]),
+            batches[0].upgrades[0].requires
+        );
+    }
+
+    fn planned(name: &str, breaking: bool, requires: &[&str]) -> PlannedUpgrade {
+        PlannedUpgrade {
+            name: name.to_owned(),
+            project: Version::new(0, 1, 0),
+            target: Version::new(1, 0, 0),
+            breaking,
+            members: BTreeSet::from(["a".to_owned()]),
+            requires: requires.iter().map(|r| r.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn batches() {
+        let batches = plan_batches(vec![
+            planned("hyper", true, &["tokio"]),
+            planned("log", false, &[]),
+            planned("rand", true, &[]),
+            planned("tokio", true, &[]),
+            planned("tokio-util", false, &["tokio"]),
+        ]);
+        assert_eq!(
+            vec![
+                vec!["log"],
+                vec!["rand"],
+                vec!["hyper", "tokio", "tokio-util"]
+            ],
+            names(&batches)
+        );
+        assert!(!batches[0].breaking);
+        assert!(batches[2].breaking);
+    }
+}
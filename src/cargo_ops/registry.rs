@@ -0,0 +1,49 @@
+use std::{collections::HashSet, task::Poll};
+
+use cargo::{
+    core::{Dependency, SourceId, Summary},
+    sources::{
+        config::SourceConfigMap,
+        source::{QueryKind, Source},
+    },
+    util::{CargoResult, cache_lock::CacheLockMode, context::GlobalContext},
+};
+
+/// Query all versions of package `name` available from `source_id`, newest
+/// first, leaving out yanked versions unless `include_yanked`
+pub fn query_versions(
+    gctx: &GlobalContext,
+    source_id: SourceId,
+    name: &str,
+    include_yanked: bool,
+) -> CargoResult<Vec<Summary>> {
+    let _lock = gctx.acquire_package_cache_lock(CacheLockMode::DownloadExclusive)?;
+    let source_config = SourceConfigMap::new(gctx)?;
+    let mut source = source_config.load(source_id, &HashSet::new())?;
+    if !source_id.is_crates_io() && !source_id.is_sparse() {
+        source.invalidate_cache();
+    }
+    let dependency = Dependency::parse(name, None, source_id)?;
+    // Query the source using the same poll loop pattern as cargo itself:
+    // https://github.com/rust-lang/cargo/blob/60400187f/src/cargo/core/resolver/errors.rs#L479-L488
+    //
+    // Sources (especially sparse registries) may return Poll::Pending on
+    // the first query to initiate an async fetch. Calling
+    // block_until_ready() drives the pending I/O to completion so the
+    // next query returns Poll::Ready.
+    let query_result = loop {
+        match source.query_vec(&dependency, QueryKind::Exact) {
+            Poll::Ready(Ok(result)) => break result,
+            Poll::Ready(Err(e)) => return Err(e),
+            Poll::Pending => source.block_until_ready()?,
+        }
+    };
+    let mut query_result = query_result
+        .into_iter()
+        .filter(|index_summary| include_yanked || !index_summary.is_yanked())
+        .map(|index_summary| index_summary.into_summary())
+        .collect::<Vec<_>>();
+
+    query_result.sort_by(|a, b| b.version().cmp(a.version()));
+    Ok(query_result)
+}
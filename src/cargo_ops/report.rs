@@ -0,0 +1,126 @@
+use std::{
+    collections::BTreeMap,
+    io::{self, Write},
+};
+
+use cargo::util::CargoResult;
+use serde::Serialize;
+use tabwriter::TabWriter;
+
+/// Write `rows` as a table with aligned columns, under the column names of
+/// `header` and a line underlining them
+pub fn write_table<W: Write>(
+    out: &mut W,
+    header: &[&str],
+    rows: impl IntoIterator<Item = Vec<String>>,
+) -> CargoResult<()> {
+    let mut tw = TabWriter::new(vec![]);
+    writeln!(&mut tw, "{}", header.join("\t"))?;
+    let underline: Vec<_> = header.iter().map(|name| "-".repeat(name.len())).collect();
+    writeln!(&mut tw, "{}", underline.join("\t"))?;
+    for row in rows {
+        writeln!(&mut tw, "{}", row.join("\t"))?;
+    }
+    tw.flush()?;
+    write!(out, "{}", String::from_utf8(tw.into_inner()?)?)?;
+    out.flush()?;
+    Ok(())
+}
+
+/// Print `rows` as a table to stdout
+pub fn print_table(
+    header: &[&str],
+    rows: impl IntoIterator<Item = Vec<String>>,
+) -> CargoResult<()> {
+    write_table(&mut io::stdout().lock(), header, rows)
+}
+
+/// Print a section of the report following the main table, titled `title`
+pub fn print_section(
+    title: &str,
+    header: &[&str],
+    rows: impl IntoIterator<Item = Vec<String>>,
+) -> CargoResult<()> {
+    println!("\n{title}\n================");
+    print_table(header, rows)
+}
+
+/// A JSON record, tagged with the workspace it reports on if any
+#[derive(Serialize)]
+struct Record<'a, T: ?Sized> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    workspace_root: Option<&'a str>,
+    #[serde(flatten)]
+    value: &'a T,
+}
+
+/// Serialize `value` as a JSON record, tagged with the root manifest
+/// `workspace_root` if any
+fn json_record<T: Serialize + ?Sized>(
+    value: &T,
+    workspace_root: Option<&str>,
+) -> CargoResult<String> {
+    Ok(serde_json::to_string(&Record {
+        workspace_root,
+        value,
+    })?)
+}
+
+/// Print `value`, which serializes to an object, as a JSON line tagged with
+/// the root manifest `workspace_root` if any
+pub fn print_json<T: Serialize + ?Sized>(
+    value: &T,
+    workspace_root: Option<&str>,
+) -> CargoResult<()> {
+    println!("{}", json_record(value, workspace_root)?);
+    Ok(())
+}
+
+/// Print `value` as a JSON line, as the only field named `key` of an object
+pub fn print_json_line<T: Serialize + ?Sized>(
+    key: &str,
+    value: &T,
+    workspace_root: Option<&str>,
+) -> CargoResult<()> {
+    print_json(&BTreeMap::from([(key, value)]), workspace_root)
+}
+
+/// Format an optional value of a table, `---` standing for none
+pub fn or_none<T: ToString>(value: Option<T>) -> String {
+    value.map_or_else(|| "---".to_owned(), |value| value.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn table() {
+        let mut out = vec![];
+        write_table(
+            &mut out,
+            &["Name", "Version"],
+            [
+                vec!["log".to_owned(), "0.4.8".to_owned()],
+                vec!["rand".to_owned(), or_none(None::<&str>)],
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            "Name  Version\n----  -------\nlog   0.4.8\nrand  ---\n",
+            String::from_utf8(out).unwrap()
+        );
+    }
+
+    #[test]
+    fn json_records() {
+        let value = BTreeMap::from([("updates", ["log"])]);
+        assert_eq!(r#"{"updates":["log"]}"#, json_record(&value, None).unwrap());
+        assert_eq!(
+            r#"{"workspace_root":"/src/a/Cargo.toml","updates":["log"]}"#,
+            json_record(&value, Some("/src/a/Cargo.toml")).unwrap()
+        );
+    }
+}
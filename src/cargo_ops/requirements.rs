@@ -0,0 +1,255 @@
+use std::fmt;
+
+use cargo::util::CargoResult;
+use semver::{Comparator, Op, Version, VersionReq};
+use serde::Serialize;
+
+use super::report::{print_json_line, print_section};
+
+/// A problem with a version requirement
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Issue {
+    /// `*` or `1.*`, accepting versions the project never saw
+    Wildcard,
+    /// `=1.2.3`, preventing downstream users from getting fixes
+    ExactPin,
+    /// The lower bound is far below the locked version, so downstream users
+    /// may get old versions the project was never tested with
+    StaleLowerBound(String),
+    /// A pre-release is required while a stable version is published
+    PreRelease(String),
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Issue::Wildcard => write!(f, "wildcard"),
+            Issue::ExactPin => write!(f, "exact pin"),
+            Issue::StaleLowerBound(lower) => write!(f, "lower bound {lower} far below locked"),
+            Issue::PreRelease(stable) => write!(f, "pre-release, {stable} is stable"),
+        }
+    }
+}
+
+/// A requirement of a workspace member flagged by `--check-requirements`
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct RequirementLint {
+    pub member: String,
+    pub name: String,
+    pub requirement: String,
+    pub locked: String,
+    pub issue: Issue,
+    pub suggestion: String,
+}
+
+/// Minor versions a lower bound may lag behind the locked version by default
+pub const DEFAULT_MAX_MINOR_LAG: u64 = 3;
+
+/// The issues of `requirement`, along with the suggested replacement, given
+/// the `locked` version and the `available` versions of the dependency.
+/// Lower bounds lagging `max_minor_lag` minor versions behind are stale.
+pub fn lint_requirement(
+    requirement: &str,
+    locked: &Version,
+    available: &[&Version],
+    max_minor_lag: u64,
+) -> Vec<(Issue, String)> {
+    let Ok(req) = VersionReq::parse(requirement) else {
+        return vec![];
+    };
+    let locked_req = format!("{}.{}.{}", locked.major, locked.minor, locked.patch);
+    let mut issues = vec![];
+
+    if req.comparators.is_empty() || req.comparators.iter().any(|c| c.op == Op::Wildcard) {
+        issues.push((Issue::Wildcard, locked_req.clone()));
+    }
+    if let [comparator] = req.comparators.as_slice()
+        && comparator.op == Op::Exact
+    {
+        let suggestion = requirement.trim().trim_start_matches('=').trim().to_owned();
+        issues.push((Issue::ExactPin, suggestion));
+    }
+    let lower = req
+        .comparators
+        .iter()
+        .filter(|c| matches!(c.op, Op::Caret | Op::Tilde | Op::GreaterEq))
+        .map(lower_bound)
+        .max();
+    if let Some(lower) = lower
+        && far_below(&lower, locked, max_minor_lag)
+    {
+        issues.push((Issue::StaleLowerBound(lower.to_string()), locked_req));
+    }
+    for comparator in req.comparators.iter().filter(|c| !c.pre.is_empty()) {
+        let base = lower_bound(comparator);
+        let stable = available
+            .iter()
+            .filter(|v| v.pre.is_empty() && **v >= &base)
+            .max();
+        if let Some(stable) = stable {
+            issues.push((Issue::PreRelease(stable.to_string()), stable.to_string()));
+        }
+    }
+    issues
+}
+
+/// The lowest version matched by `comparator`, ignoring its pre-release
+fn lower_bound(comparator: &Comparator) -> Version {
+    Version::new(
+        comparator.major,
+        comparator.minor.unwrap_or(0),
+        comparator.patch.unwrap_or(0),
+    )
+}
+
+/// Whether `lower` is in an older compatibility range than `locked`, or
+/// lags behind it by `max_minor_lag` minor versions or more. Patch versions
+/// of `0.y` releases are compatible and never make a lower bound stale.
+fn far_below(lower: &Version, locked: &Version, max_minor_lag: u64) -> bool {
+    if lower >= locked {
+        false
+    } else if lower.major != locked.major {
+        true
+    } else if locked.major == 0 {
+        lower.minor != locked.minor || (locked.minor == 0 && lower.patch != locked.patch)
+    } else {
+        locked.minor - lower.minor >= max_minor_lag
+    }
+}
+
+/// Print the requirements section of the report, if any is flagged
+pub fn print_requirements(lints: &[RequirementLint]) -> CargoResult<()> {
+    if lints.is_empty() {
+        return Ok(());
+    }
+    print_section(
+        "Requirements",
+        &[
+            "Member",
+            "Name",
+            "Requirement",
+            "Locked",
+            "Issue",
+            "Suggested",
+        ],
+        lints.iter().map(|lint| {
+            vec![
+                lint.member.clone(),
+                lint.name.clone(),
+                lint.requirement.clone(),
+                lint.locked.clone(),
+                lint.issue.to_string(),
+                lint.suggestion.clone(),
+            ]
+        }),
+    )
+}
+
+/// Print the flagged requirements as a JSON line, if any
+pub fn print_requirements_json(
+    lints: &[RequirementLint],
+    workspace_root: Option<&str>,
+) -> CargoResult<()> {
+    if !lints.is_empty() {
+        print_json_line("requirements", lints, workspace_root)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    fn lint(requirement: &str, locked: &str, available: &[&str]) -> Vec<(Issue, String)> {
+        let available: Vec<_> = available
+            .iter()
+            .map(|v| Version::parse(v).unwrap())
+            .collect();
+        let available: Vec<_> = available.iter().collect();
+        lint_requirement(
+            requirement,
+            &Version::parse(locked).unwrap(),
+            &available,
+            DEFAULT_MAX_MINOR_LAG,
+        )
+    }
+
+    #[test]
+    fn wildcard() {
+        assert_eq!(
+            vec![(Issue::Wildcard, "1.2.3".to_owned())],
+            lint("*", "1.2.3", &[])
+        );
+        assert_eq!(
+            vec![(Issue::Wildcard, "1.2.3".to_owned())],
+            lint("1.*", "1.2.3", &[])
+        );
+    }
+
+    #[test]
+    fn exact() {
+        assert_eq!(
+            vec![(Issue::ExactPin, "1.0.50".to_owned())],
+            lint("=1.0.50", "1.0.50", &[])
+        );
+    }
+
+    #[test]
+    fn stale_lower_bound() {
+        assert_eq!(
+            vec![(
+                Issue::StaleLowerBound("1.0.0".to_owned()),
+                "1.9.3".to_owned()
+            )],
+            lint("1", "1.9.3", &[])
+        );
+        assert_eq!(
+            vec![(
+                Issue::StaleLowerBound("0.3.0".to_owned()),
+                "0.4.8".to_owned()
+            )],
+            lint(">=0.3", "0.4.8", &[])
+        );
+        assert!(lint("0.4", "0.4.8", &[]).is_empty());
+        assert!(lint("1.9", "1.9.3", &[]).is_empty());
+        assert!(!lint(">=1.9, <3", "2.0.1", &[]).is_empty());
+        assert!(lint("~0.7", "0.7.0", &[]).is_empty());
+    }
+
+    #[test]
+    fn stale_lower_bound_threshold() {
+        let far = |lower: &str, locked: &str, lag: u64| {
+            far_below(
+                &Version::parse(lower).unwrap(),
+                &Version::parse(locked).unwrap(),
+                lag,
+            )
+        };
+        assert!(far("1.6.0", "1.9.3", 3));
+        assert!(!far("1.7.0", "1.9.3", 3));
+        assert!(far("1.8.0", "1.9.3", 1));
+        assert!(!far("1.9.0", "1.9.3", 1));
+        assert!(!far("0.4.0", "0.4.18", 0));
+        // breaking gaps regardless of the threshold
+        assert!(far("1.9.0", "2.0.0", 100));
+        assert!(far("0.3.9", "0.4.0", 100));
+        assert!(far("0.0.1", "0.0.2", 100));
+        assert!(!far("2.0.0", "1.9.3", 0));
+    }
+
+    #[test]
+    fn pre_release() {
+        assert_eq!(
+            vec![(Issue::PreRelease("1.0.1".to_owned()), "1.0.1".to_owned())],
+            lint(
+                "1.0.0-rc.1",
+                "1.0.0-rc.1",
+                &["1.0.0-rc.1", "1.0.0", "1.0.1"]
+            )
+        );
+        assert!(lint("2.0.0-rc.1", "2.0.0-rc.1", &["1.0.1", "2.0.0-rc.1"]).is_empty());
+    }
+}
@@ -0,0 +1,63 @@
+use std::fs;
+
+use cargo::util::CargoResult;
+use tempfile::{Builder, TempDir};
+use toml::{Value, value::Table};
+
+/// Name of the package of scratch projects
+const SCRATCH_NAME: &str = "cargo-outdated-scratch";
+
+/// Create a project depending on the crate given to `--crate` as
+/// `name@version`, or on its latest version when it comes without one
+pub fn scratch_project(spec: &str) -> CargoResult<TempDir> {
+    let dir = Builder::new().prefix("cargo-outdated").tempdir()?;
+    fs::write(dir.path().join("Cargo.toml"), scratch_manifest(spec)?)?;
+    fs::write(dir.path().join("lib.rs"), "")?;
+    Ok(dir)
+}
+
+/// The manifest of a scratch project depending on `spec` alone
+fn scratch_manifest(spec: &str) -> CargoResult<String> {
+    let (name, requirement) = match spec.split_once('@') {
+        Some((name, version)) => (name, format!("={version}")),
+        None => (spec, "*".to_owned()),
+    };
+    let mut package = Table::new();
+    package.insert("name".to_owned(), Value::from(SCRATCH_NAME));
+    package.insert("version".to_owned(), Value::from("0.0.0"));
+    package.insert("edition".to_owned(), Value::from("2021"));
+    package.insert("publish".to_owned(), Value::from(false));
+    let mut lib = Table::new();
+    lib.insert("path".to_owned(), Value::from("lib.rs"));
+    let mut dependencies = Table::new();
+    dependencies.insert(name.to_owned(), Value::from(requirement));
+
+    let mut manifest = Table::new();
+    manifest.insert("package".to_owned(), Value::Table(package));
+    manifest.insert("lib".to_owned(), Value::Table(lib));
+    manifest.insert("dependencies".to_owned(), Value::Table(dependencies));
+    // keep the project out of any enclosing workspace
+    manifest.insert("workspace".to_owned(), Value::Table(Table::new()));
+    Ok(::toml::to_string(&manifest)?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    fn dependencies(spec: &str) -> Value {
+        let manifest: Table = ::toml::from_str(&scratch_manifest(spec).unwrap()).unwrap();
+        manifest["dependencies"].clone()
+    }
+
+    #[test]
+    fn manifest() {
+        assert_eq!(
+            Some("=1.0.100"),
+            dependencies("serde_json@1.0.100")["serde_json"].as_str()
+        );
+        assert_eq!(Some("*"), dependencies("serde_json")["serde_json"].as_str());
+    }
+}
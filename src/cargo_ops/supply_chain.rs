@@ -0,0 +1,174 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
+
+use cargo::util::CargoResult;
+use semver::Version;
+use serde::Serialize;
+
+use super::{
+    ElaborateWorkspace,
+    delta::{PackageDelta, package_versions},
+    report::{print_json_line, print_section},
+};
+
+/// Which upgrades introduce or drop a crate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Upgrades {
+    Compat,
+    Latest,
+}
+
+impl fmt::Display for Upgrades {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Upgrades::Compat => write!(f, "compat"),
+            Upgrades::Latest => write!(f, "latest"),
+        }
+    }
+}
+
+/// A crate introduced or dropped by taking some upgrades
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct CrateChange {
+    pub name: String,
+    pub version: String,
+    pub upgrades: Upgrades,
+    /// Whether the crate is introduced rather than dropped
+    pub added: bool,
+}
+
+/// Crates which are introduced or dropped when taking the compatible
+/// upgrades of `ela_compat` or the latest ones of `ela_latest`, compared to
+/// the current workspace `ela_curr`
+pub fn supply_chain_changes(
+    ela_curr: &ElaborateWorkspace<'_>,
+    ela_compat: &ElaborateWorkspace<'_>,
+    ela_latest: &ElaborateWorkspace<'_>,
+) -> Vec<CrateChange> {
+    crate_changes(
+        &package_versions(ela_curr),
+        &package_versions(ela_compat),
+        &package_versions(ela_latest),
+    )
+}
+
+/// Crates which are introduced or dropped between the versions of crates by
+/// name `curr` and those of `compat` or `latest`
+fn crate_changes(
+    curr: &BTreeMap<String, BTreeSet<Version>>,
+    compat: &BTreeMap<String, BTreeSet<Version>>,
+    latest: &BTreeMap<String, BTreeSet<Version>>,
+) -> Vec<CrateChange> {
+    let mut changes = vec![];
+    for (upgrades, versions) in [(Upgrades::Compat, compat), (Upgrades::Latest, latest)] {
+        let delta = PackageDelta::from_versions(curr, versions);
+        let added = delta.added.into_iter().map(|c| (c, true));
+        let removed = delta.removed.into_iter().map(|c| (c, false));
+        changes.extend(added.chain(removed).map(|(c, added)| CrateChange {
+            name: c.name,
+            version: c.version,
+            upgrades,
+            added,
+        }));
+    }
+    changes.sort();
+    changes
+}
+
+/// Print the supply chain section of the report, if any crate is introduced
+/// or dropped
+pub fn print_supply_chain(changes: &[CrateChange]) -> CargoResult<()> {
+    if changes.is_empty() {
+        return Ok(());
+    }
+    print_section(
+        "Supply chain",
+        &["Name", "Version", "Upgrades", "Change"],
+        changes.iter().map(|change| {
+            vec![
+                change.name.clone(),
+                change.version.clone(),
+                change.upgrades.to_string(),
+                if change.added { "New" } else { "Dropped" }.to_owned(),
+            ]
+        }),
+    )
+}
+
+/// Print the crates introduced or dropped as a JSON line, if any
+pub fn print_supply_chain_json(
+    changes: &[CrateChange],
+    workspace_root: Option<&str>,
+) -> CargoResult<()> {
+    if !changes.is_empty() {
+        print_json_line("supply_chain", changes, workspace_root)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    fn versions(crates: &[(&str, &str)]) -> BTreeMap<String, BTreeSet<Version>> {
+        let mut versions: BTreeMap<String, BTreeSet<Version>> = BTreeMap::new();
+        for (name, version) in crates {
+            versions
+                .entry(name.to_string())
+                .or_default()
+                .insert(Version::parse(version).unwrap());
+        }
+        versions
+    }
+
+    fn change(name: &str, version: &str, upgrades: Upgrades, added: bool) -> CrateChange {
+        CrateChange {
+            name: name.to_owned(),
+            version: version.to_owned(),
+            upgrades,
+            added,
+        }
+    }
+
+    #[test]
+    fn changes() {
+        // log is only bumped, so it is neither introduced nor dropped
+        let changes = crate_changes(
+            &versions(&[
+                ("hyper", "0.14.28"),
+                ("log", "0.4.8"),
+                ("net2", "0.2.39"),
+                ("rand", "0.7.3"),
+            ]),
+            &versions(&[
+                ("hyper", "0.14.30"),
+                ("log", "0.4.22"),
+                ("rand", "0.7.3"),
+                ("socket2", "0.5.7"),
+            ]),
+            &versions(&[
+                ("h2", "0.4.5"),
+                ("hyper", "1.4.1"),
+                ("log", "0.4.22"),
+                ("socket2", "0.5.7"),
+            ]),
+        );
+
+        assert_eq!(
+            vec![
+                change("h2", "0.4.5", Upgrades::Latest, true),
+                change("net2", "0.2.39", Upgrades::Compat, false),
+                change("net2", "0.2.39", Upgrades::Latest, false),
+                change("rand", "0.7.3", Upgrades::Latest, false),
+                change("socket2", "0.5.7", Upgrades::Compat, true),
+                change("socket2", "0.5.7", Upgrades::Latest, true),
+            ],
+            changes
+        );
+    }
+}
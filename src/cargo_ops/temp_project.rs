@@ -1,30 +1,34 @@
 use std::{
     cell::RefCell,
-    collections::HashSet,
+    collections::{BTreeMap, BTreeSet, HashSet},
     env,
     fs::{self, File, OpenOptions},
     io::{Read, Write},
     path::{Path, PathBuf},
     rc::Rc,
-    task::Poll,
 };
 
 use anyhow::{Context, anyhow};
 use cargo::{
-    core::{Dependency, PackageId, Summary, Verbosity, Workspace},
-    ops::{UpdateOptions, update_lockfile},
-    sources::{
-        config::SourceConfigMap,
-        source::{QueryKind, Source},
+    core::{
+        Dependency, PackageId, Summary, Verbosity, Workspace, dependency::DepKind,
+        resolver::ResolveError,
     },
-    util::{CargoResult, cache_lock::CacheLockMode, context::GlobalContext},
+    ops::{UpdateOptions, update_lockfile},
+    util::{CargoResult, context::GlobalContext},
 };
 use semver::{Version, VersionReq};
 use tempfile::{Builder, TempDir};
 use toml::{Value, value::Table};
 
-use super::{ElaborateWorkspace, Manifest};
-use crate::{Options, error::OutdatedError};
+use super::{
+    ElaborateWorkspace, Manifest,
+    features::activates_dependency,
+    minimal::MinimalPin,
+    registry::query_versions,
+    requirements::{DEFAULT_MAX_MINOR_LAG, RequirementLint, lint_requirement},
+};
+use crate::{Options, error::OutdatedError, pattern::PatternSet};
 
 /// A temporary project
 pub struct TempProject<'tmp> {
@@ -35,6 +39,20 @@ pub struct TempProject<'tmp> {
     relative_manifest: String,
     options: &'tmp Options,
     is_workspace_project: bool,
+    /// Patterns of dependencies to exclude from building
+    exclude: PatternSet,
+    /// Direct dependencies whose requirement has been rewritten to a newer
+    /// latest version
+    rewritten: RefCell<BTreeSet<String>>,
+    /// Requirements flagged while looking for latest versions, with
+    /// `--check-requirements`
+    requirement_lints: RefCell<BTreeSet<RequirementLint>>,
+    /// Direct dependencies pinned to the lowest version allowed by their
+    /// requirement
+    pins: RefCell<BTreeSet<MinimalPin>>,
+    /// Whether to suppress warnings, for projects only used to probe the
+    /// resolvability of some requirements
+    silent: bool,
 }
 
 impl<'tmp> TempProject<'tmp> {
@@ -157,7 +175,7 @@ impl<'tmp> TempProject<'tmp> {
         }
 
         let relative_manifest = String::from(&orig_manifest[workspace_root_str.len() + 1..]);
-        let context = Self::generate_context(temp_dir.path(), &relative_manifest, options)?;
+        let context = Self::generate_context(temp_dir.path(), &relative_manifest, options, &[])?;
 
         Ok(TempProject {
             workspace: Rc::new(RefCell::new(None)),
@@ -167,6 +185,11 @@ impl<'tmp> TempProject<'tmp> {
             relative_manifest,
             options,
             is_workspace_project: orig_workspace.workspace_mode,
+            exclude: PatternSet::new(&options.exclude)?,
+            rewritten: RefCell::new(BTreeSet::new()),
+            requirement_lints: RefCell::new(BTreeSet::new()),
+            pins: RefCell::new(BTreeSet::new()),
+            silent: false,
         })
     }
 
@@ -174,6 +197,7 @@ impl<'tmp> TempProject<'tmp> {
         root: &Path,
         relative_manifest: &str,
         options: &Options,
+        unstable_flags: &[&str],
     ) -> CargoResult<GlobalContext> {
         let shell = ::cargo::core::Shell::new();
         let cwd = env::current_dir()
@@ -192,21 +216,57 @@ impl<'tmp> TempProject<'tmp> {
         // if it is, set it in the configure options
         let cargo_home_path = std::env::var_os("CARGO_HOME").map(std::path::PathBuf::from);
 
+        // config files given on the command line are relative to the
+        // directory cargo-outdated is run from, not the temporary project
+        let cli_config: Vec<String> = options
+            .config
+            .iter()
+            .map(|config| match fs::canonicalize(config) {
+                Ok(path) if path.is_file() => path.to_string_lossy().into_owned(),
+                _ => config.clone(),
+            })
+            .collect();
+        // the flags cargo-outdated needs itself are allowed regardless of the
+        // toolchain, those of the user only where it allows them
+        let internal_flags = !unstable_flags.is_empty();
+        let unstable_flags: Vec<String> = options
+            .unstable_flags
+            .iter()
+            .cloned()
+            .chain(unstable_flags.iter().map(|&flag| flag.to_owned()))
+            .collect();
+
+        // the lock file of the temporary project is always updated, so
+        // `--locked` only applies to the project itself while the network
+        // restriction of `--frozen` applies everywhere
         let mut context = GlobalContext::new(shell, cwd, homedir);
+        context.nightly_features_allowed |= internal_flags;
         context.configure(
             0,
             options.verbose == 0,
             Some(&options.color.to_string().to_ascii_lowercase()),
-            options.frozen(),
-            options.locked(),
-            options.offline,
+            false,
+            false,
+            options.offline || options.frozen,
             &cargo_home_path,
-            &[],
-            &[],
+            &unstable_flags,
+            &cli_config,
         )?;
         Ok(context)
     }
 
+    /// Resolve transitive dependencies to their minimal versions as well,
+    /// with the unstable `minimal-versions` resolution of Cargo
+    pub fn resolve_minimal_versions(&mut self) -> CargoResult<()> {
+        self.context = Self::generate_context(
+            self.temp_dir.path(),
+            &self.relative_manifest,
+            self.options,
+            &["minimal-versions"],
+        )?;
+        Ok(())
+    }
+
     /// Run `cargo update` against the temporary project
     pub fn cargo_update(&self) -> CargoResult<()> {
         let update_opts = UpdateOptions {
@@ -227,6 +287,92 @@ impl<'tmp> TempProject<'tmp> {
         Ok(())
     }
 
+    /// Direct dependencies whose requirement has been rewritten to a newer
+    /// latest version
+    pub fn rewritten(&self) -> Vec<String> { self.rewritten.borrow().iter().cloned().collect() }
+
+    /// Direct dependencies pinned by `write_manifest_minimal`
+    pub fn pins(&self) -> Vec<MinimalPin> { self.pins.borrow().iter().cloned().collect() }
+
+    /// Requirements flagged by `--check-requirements`
+    pub fn requirement_lints(&self) -> Vec<RequirementLint> {
+        self.requirement_lints.borrow().iter().cloned().collect()
+    }
+
+    /// Bisect the `upgrades` of a project which failed to resolve with
+    /// `error`, returning those to hold back to their SemVer requirements
+    /// along with what each of them conflicts with. `resolves_without`
+    /// probes whether the project resolves when holding back the given
+    /// upgrades.
+    pub fn bisect_unresolvable<F>(
+        upgrades: Vec<String>,
+        error: anyhow::Error,
+        mut resolves_without: F,
+    ) -> CargoResult<BTreeMap<String, String>>
+    where
+        F: FnMut(&HashSet<String>) -> CargoResult<bool>,
+    {
+        // e.g. network errors say nothing about the upgrades
+        if !is_resolve_conflict(&error) {
+            return Err(error);
+        }
+        let mut remaining = upgrades;
+        let mut unresolvable = BTreeMap::new();
+        // whether the project resolves with only `upgraded` of the remaining
+        // upgrades, besides those already found unresolvable
+        let mut resolves = |unresolvable: &BTreeMap<String, String>,
+                            remaining: &[String],
+                            upgraded: &[String]|
+         -> CargoResult<bool> {
+            let held_back: HashSet<String> = unresolvable
+                .keys()
+                .chain(remaining.iter().filter(|r| !upgraded.contains(r)))
+                .cloned()
+                .collect();
+            resolves_without(&held_back)
+        };
+
+        // the upgrades are not to blame if holding all of them back fails too
+        if !resolves(&unresolvable, &remaining, &[])? {
+            return Err(error);
+        }
+
+        while !resolves(&unresolvable, &remaining, &remaining)? {
+            // the shortest failing prefix of the upgrades ends with a culprit
+            let (mut lo, mut hi) = (1, remaining.len());
+            while lo < hi {
+                let mid = (lo + hi) / 2;
+                if resolves(&unresolvable, &remaining, &remaining[..mid])? {
+                    lo = mid + 1;
+                } else {
+                    hi = mid;
+                }
+            }
+            let culprit = remaining.remove(lo - 1);
+            let mut upgraded = remaining[..lo - 1].to_vec();
+
+            // likewise, the shortest prefix of the upgrades before it failing
+            // along with it ends with what it conflicts with
+            upgraded.insert(0, culprit.clone());
+            let conflict = if !resolves(&unresolvable, &remaining, &upgraded[..1])? {
+                "the current dependencies".to_owned()
+            } else {
+                let (mut lo, mut hi) = (2, upgraded.len());
+                while lo < hi {
+                    let mid = (lo + hi) / 2;
+                    if resolves(&unresolvable, &remaining, &upgraded[..mid])? {
+                        lo = mid + 1;
+                    } else {
+                        hi = mid;
+                    }
+                }
+                upgraded[lo - 1].clone()
+            };
+            unresolvable.insert(culprit, conflict);
+        }
+        Ok(unresolvable)
+    }
+
     fn write_manifest<P: AsRef<Path>>(manifest: &Manifest, path: P) -> CargoResult<()> {
         let mut file = File::create(path)?;
         let serialized = ::toml::to_string(manifest).expect("Failed to serialized Cargo.toml");
@@ -234,29 +380,33 @@ impl<'tmp> TempProject<'tmp> {
         Ok(())
     }
 
+    /// Apply `f` to every dependency table of the manifest along with the
+    /// kind of dependencies it declares (`[patch]` tables count as normal)
     fn manipulate_dependencies<F>(manifest: &mut Manifest, f: &mut F) -> CargoResult<()>
     where
-        F: FnMut(&mut Table) -> CargoResult<()>,
+        F: FnMut(&mut Table, DepKind) -> CargoResult<()>,
     {
         if let Some(dep) = manifest.dependencies.as_mut() {
-            f(dep)?;
+            f(dep, DepKind::Normal)?;
         }
         if let Some(dep) = manifest.dev_dependencies.as_mut() {
-            f(dep)?;
+            f(dep, DepKind::Development)?;
         }
         if let Some(dep) = manifest.build_dependencies.as_mut() {
-            f(dep)?;
+            f(dep, DepKind::Build)?;
         }
         if let Some(t) = manifest.target.as_mut() {
             for (_key, target) in t.iter_mut() {
                 if let Value::Table(ref mut target) = *target {
-                    for dependency_tables in
-                        &["dependencies", "dev-dependencies", "build-dependencies"]
-                    {
+                    for (dependency_tables, kind) in &[
+                        ("dependencies", DepKind::Normal),
+                        ("dev-dependencies", DepKind::Development),
+                        ("build-dependencies", DepKind::Build),
+                    ] {
                         if let Some(&mut Value::Table(ref mut dep_table)) =
                             target.get_mut(*dependency_tables)
                         {
-                            f(dep_table)?;
+                            f(dep_table, *kind)?;
                         }
                     }
                 }
@@ -265,28 +415,33 @@ impl<'tmp> TempProject<'tmp> {
         if let Some(t) = manifest.patch.as_mut() {
             for (_key, patch) in t.iter_mut() {
                 if let Value::Table(ref mut patch) = *patch {
-                    f(patch)?;
+                    f(patch, DepKind::Normal)?;
                 }
             }
         }
         Ok(())
     }
 
-    /// Write manifests with SemVer requirements
-    pub fn write_manifest_semver<P: AsRef<Path>>(
+    /// Write the manifests of the temporary project, making them buildable
+    /// from its directory and applying `rewrite` to each dependency table
+    /// along with its kind and the name and features of its package
+    fn rewrite_manifests<P, F>(
         &'tmp self,
         orig_root: P,
         tmp_root: P,
-        workspace: &ElaborateWorkspace<'_>,
         skipped: &mut HashSet<String>,
-    ) -> CargoResult<()> {
+        mut rewrite: F,
+    ) -> CargoResult<()>
+    where
+        P: AsRef<Path>,
+        F: FnMut(&mut Table, DepKind, &str, &Option<Value>) -> CargoResult<()>,
+    {
         let bin = {
             let mut bin = Table::new();
             bin.insert("name".to_owned(), Value::String("test".to_owned()));
             bin.insert("path".to_owned(), Value::String("test.rs".to_owned()));
             bin
         };
-
         for manifest_path in &self.manifest_paths {
             let mut manifest: Manifest = {
                 let mut buf = String::new();
@@ -300,7 +455,7 @@ impl<'tmp> TempProject<'tmp> {
             if let Some(lib) = manifest.lib.as_mut() {
                 lib.insert("path".to_owned(), Value::String("test_lib.rs".to_owned()));
             }
-            Self::manipulate_dependencies(&mut manifest, &mut |deps| {
+            Self::manipulate_dependencies(&mut manifest, &mut |deps, _kind| {
                 Self::replace_path_with_absolute(
                     self,
                     deps,
@@ -313,70 +468,229 @@ impl<'tmp> TempProject<'tmp> {
 
             let package_name = manifest.name();
             let features = manifest.features.clone();
-            Self::manipulate_dependencies(&mut manifest, &mut |deps| {
-                self.update_version_and_feature(deps, &features, workspace, &package_name, false)
+            Self::manipulate_dependencies(&mut manifest, &mut |deps, kind| {
+                rewrite(deps, kind, &package_name, &features)
             })?;
 
             Self::write_manifest(&manifest, manifest_path)?;
         }
-        let root_manifest = self.temp_dir.path().join(&self.relative_manifest);
+        Ok(())
+    }
 
+    /// Load the workspace of the temporary project from its written manifests
+    fn load_workspace(&'tmp self) -> CargoResult<()> {
+        let root_manifest = self.temp_dir.path().join(&self.relative_manifest);
         *self.workspace.borrow_mut() =
             Some(Workspace::new(Path::new(&root_manifest), &self.context)?);
         Ok(())
     }
 
-    /// Write manifests with wildcard requirements
-    pub fn write_manifest_latest<P: AsRef<Path>>(
+    /// Write manifests with SemVer requirements
+    pub fn write_manifest_semver<P: AsRef<Path>>(
         &'tmp self,
         orig_root: P,
         tmp_root: P,
         workspace: &ElaborateWorkspace<'_>,
         skipped: &mut HashSet<String>,
     ) -> CargoResult<()> {
-        let bin = {
-            let mut bin = Table::new();
-            bin.insert("name".to_owned(), Value::String("test".to_owned()));
-            bin.insert("path".to_owned(), Value::String("test.rs".to_owned()));
-            bin
-        };
-        for manifest_path in &self.manifest_paths {
-            let mut manifest: Manifest = {
-                let mut buf = String::new();
-                let mut file = File::open(manifest_path)?;
-                file.read_to_string(&mut buf)?;
-                ::toml::from_str(&buf)?
-            };
+        self.rewrite_manifests(
+            orig_root,
+            tmp_root,
+            skipped,
+            |deps, kind, package_name, features| {
+                // dependencies of unchecked kinds are left untouched, which
+                // saves querying the registry for them
+                if !self.options.includes_kind(kind) {
+                    return Ok(());
+                }
+                self.update_version_and_feature(
+                    deps,
+                    features,
+                    workspace,
+                    package_name,
+                    false,
+                    &HashSet::new(),
+                )
+            },
+        )?;
+        self.load_workspace()
+    }
 
-            manifest.bin = Some(vec![bin.clone()]);
-            // provide lib.path
-            if let Some(lib) = manifest.lib.as_mut() {
-                lib.insert("path".to_owned(), Value::String("test_lib.rs".to_owned()));
-            }
-            Self::manipulate_dependencies(&mut manifest, &mut |deps| {
-                Self::replace_path_with_absolute(
-                    self,
+    /// Write manifests with wildcard requirements, except for the `held_back`
+    /// dependencies which keep their SemVer requirements
+    pub fn write_manifest_latest<P: AsRef<Path>>(
+        &'tmp self,
+        orig_root: P,
+        tmp_root: P,
+        workspace: &ElaborateWorkspace<'_>,
+        skipped: &mut HashSet<String>,
+        held_back: &HashSet<String>,
+    ) -> CargoResult<()> {
+        self.rewrite_manifests(
+            orig_root,
+            tmp_root,
+            skipped,
+            |deps, kind, package_name, features| {
+                if !self.options.includes_kind(kind) {
+                    return Ok(());
+                }
+                self.update_version_and_feature(
                     deps,
-                    orig_root.as_ref(),
-                    tmp_root.as_ref(),
-                    manifest_path,
-                    skipped,
+                    features,
+                    workspace,
+                    package_name,
+                    true,
+                    held_back,
                 )
-            })?;
+            },
+        )?;
+        self.load_workspace()
+    }
 
-            let package_name = manifest.name();
-            let features = manifest.features.clone();
-            Self::manipulate_dependencies(&mut manifest, &mut |deps| {
-                self.update_version_and_feature(deps, &features, workspace, &package_name, true)
-            })?;
+    /// Write manifests pinning direct dependencies to the lowest version
+    /// allowed by their requirements, except for the `held_back` ones
+    pub fn write_manifest_minimal<P: AsRef<Path>>(
+        &'tmp self,
+        orig_root: P,
+        tmp_root: P,
+        workspace: &ElaborateWorkspace<'_>,
+        skipped: &mut HashSet<String>,
+        held_back: &HashSet<String>,
+    ) -> CargoResult<()> {
+        let mut lowest = |member: &str, dep_key: &str, dependency: &Value, requirement: &str| {
+            self.lowest_version(
+                workspace,
+                held_back,
+                member,
+                dep_key,
+                dependency,
+                requirement,
+            )
+        };
+        let mut inherited = BTreeMap::new();
+        self.rewrite_manifests(
+            orig_root,
+            tmp_root,
+            skipped,
+            |deps, kind, package_name, _| {
+                if !self.options.includes_kind(kind) {
+                    return Ok(());
+                }
+                let pins = pin_dependencies(deps, package_name, &mut inherited, &mut lowest)?;
+                self.pins.borrow_mut().extend(pins);
+                Ok(())
+            },
+        )?;
 
-            Self::write_manifest(&manifest, manifest_path)?;
+        // requirements inherited from `[workspace.dependencies]`
+        let root_manifest = self.temp_dir.path().join("Cargo.toml");
+        let mut root: Table = ::toml::from_str(&fs::read_to_string(&root_manifest)?)?;
+        let pins = pin_inherited(&mut root, &inherited, &mut lowest)?;
+        if !pins.is_empty() {
+            fs::write(&root_manifest, ::toml::to_string(&root)?)?;
+            self.pins.borrow_mut().extend(pins);
         }
+        self.load_workspace()
+    }
 
-        let root_manifest = self.temp_dir.path().join(&self.relative_manifest);
-        *self.workspace.borrow_mut() =
-            Some(Workspace::new(Path::new(&root_manifest), &self.context)?);
-        Ok(())
+    /// Write manifests pinning the `assumed` direct dependencies to the given
+    /// versions, leaving other requirements untouched
+    pub fn write_manifest_assumed<P: AsRef<Path>>(
+        &'tmp self,
+        orig_root: P,
+        tmp_root: P,
+        skipped: &mut HashSet<String>,
+        assumed: &BTreeMap<String, Version>,
+    ) -> CargoResult<()> {
+        let mut applied = BTreeSet::new();
+        self.rewrite_manifests(orig_root, tmp_root, skipped, |deps, _, _, _| {
+            Self::assume_versions(deps, assumed, &mut applied);
+            Ok(())
+        })?;
+
+        // requirements inherited from `[workspace.dependencies]`
+        let root_manifest = self.temp_dir.path().join("Cargo.toml");
+        let mut root: Table = ::toml::from_str(&fs::read_to_string(&root_manifest)?)?;
+        if let Some(Value::Table(deps)) = root
+            .get_mut("workspace")
+            .and_then(|workspace| workspace.get_mut("dependencies"))
+        {
+            Self::assume_versions(deps, assumed, &mut applied);
+            fs::write(&root_manifest, ::toml::to_string(&root)?)?;
+        }
+
+        if let Some(name) = assumed.keys().find(|name| !applied.contains(*name)) {
+            return Err(anyhow!(
+                "cannot assume a version of {name}, which is not a direct registry dependency"
+            ));
+        }
+        self.load_workspace()
+    }
+
+    /// Pin the registry dependencies of a table found in `assumed` to the
+    /// assumed version, recording them as `applied`
+    fn assume_versions(
+        dependencies: &mut Table,
+        assumed: &BTreeMap<String, Version>,
+        applied: &mut BTreeSet<String>,
+    ) {
+        for (dep_key, dependency) in dependencies.iter_mut() {
+            let package = match dependency.get("package") {
+                Some(Value::String(package)) => package.clone(),
+                _ => dep_key.clone(),
+            };
+            let Some(version) = assumed.get(&package) else {
+                continue;
+            };
+            let requirement = Value::String(format!("={version}"));
+            match dependency {
+                Value::String(_) => *dependency = requirement,
+                Value::Table(t)
+                    if !["git", "path", "workspace"]
+                        .iter()
+                        .any(|key| t.contains_key(*key)) =>
+                {
+                    t.insert("version".to_owned(), requirement);
+                }
+                _ => continue,
+            }
+            applied.insert(package);
+        }
+    }
+
+    /// The lowest published version matching `requirement`, the requirement
+    /// of the registry dependency `dep_key` of `member`, unless it is
+    /// excluded or `held_back`
+    fn lowest_version(
+        &self,
+        workspace: &ElaborateWorkspace<'_>,
+        held_back: &HashSet<String>,
+        member: &str,
+        dep_key: &str,
+        dependency: &Value,
+        requirement: &str,
+    ) -> CargoResult<Option<Version>> {
+        let package = dependency_package(dep_key, dependency);
+        if self.is_excluded(dep_key, Some(dependency)) || held_back.contains(&package) {
+            return Ok(None);
+        }
+        let Ok(req) = VersionReq::parse(requirement) else {
+            return Ok(None);
+        };
+        let Ok(package_id) = workspace.find_direct_dependency(&package, member) else {
+            return Ok(None);
+        };
+        let source_id = package_id.source_id();
+        if !source_id.is_registry() {
+            return Ok(None);
+        }
+        let versions = query_versions(
+            workspace.workspace.gctx(),
+            source_id.with_locked_precise(),
+            &package,
+            false,
+        )?;
+        Ok(lowest_matching(&req, versions.iter().map(Summary::version)).cloned())
     }
 
     fn find_update(
@@ -390,37 +704,26 @@ impl<'tmp> TempProject<'tmp> {
         let package_id = workspace.find_direct_dependency(name, dependent_package_name)?;
         let version = package_id.version();
         let source_id = package_id.source_id().with_locked_precise();
-        let query_result = {
-            let ws_context = workspace.workspace.gctx();
-            let _lock = ws_context.acquire_package_cache_lock(CacheLockMode::DownloadExclusive)?;
-            let source_config = SourceConfigMap::new(ws_context)?;
-            let mut source = source_config.load(source_id, &HashSet::new())?;
-            if !source_id.is_crates_io() && !source_id.is_sparse() {
-                source.invalidate_cache();
+        let query_result = query_versions(workspace.workspace.gctx(), source_id, name, true)?;
+        if find_latest
+            && self.options.check_requirements
+            && let Some(requirement) = requirement
+        {
+            let available: Vec<_> = query_result.iter().map(Summary::version).collect();
+            let max_minor_lag = self.options.max_minor_lag.unwrap_or(DEFAULT_MAX_MINOR_LAG);
+            for (issue, suggestion) in
+                lint_requirement(requirement, version, &available, max_minor_lag)
+            {
+                self.requirement_lints.borrow_mut().insert(RequirementLint {
+                    member: dependent_package_name.to_owned(),
+                    name: name.to_owned(),
+                    requirement: requirement.to_owned(),
+                    locked: version.to_string(),
+                    issue,
+                    suggestion,
+                });
             }
-            let dependency = Dependency::parse(name, None, source_id)?;
-            // Query the source using the same poll loop pattern as cargo itself:
-            // https://github.com/rust-lang/cargo/blob/60400187f/src/cargo/core/resolver/errors.rs#L479-L488
-            //
-            // Sources (especially sparse registries) may return Poll::Pending on
-            // the first query to initiate an async fetch. Calling
-            // block_until_ready() drives the pending I/O to completion so the
-            // next query returns Poll::Ready.
-            let query_result = loop {
-                match source.query_vec(&dependency, QueryKind::Exact) {
-                    Poll::Ready(Ok(result)) => break result,
-                    Poll::Ready(Err(e)) => return Err(e),
-                    Poll::Pending => source.block_until_ready()?,
-                }
-            };
-            let mut query_result = query_result
-                .into_iter()
-                .map(|index_summary| index_summary.into_summary())
-                .collect::<Vec<_>>();
-
-            query_result.sort_by(|a, b| b.version().cmp(a.version()));
-            query_result
-        };
+        }
         let version_req = match requirement {
             Some(requirement) => Some(VersionReq::parse(requirement)?),
             None => None,
@@ -470,47 +773,23 @@ impl<'tmp> TempProject<'tmp> {
         Ok(latest_summary.clone())
     }
 
-    fn feature_includes(&self, name: &str, optional: bool, features_table: &Option<Value>) -> bool {
-        if self.options.all_features() {
-            return true;
-        }
-        if !optional && self.options.features.contains(&String::from("default")) {
-            return true;
-        }
-        let features_table = match *features_table {
-            Some(Value::Table(ref features_table)) => features_table,
-            _ => return false,
-        };
-        let mut to_resolve: Vec<&str> = self
-            .options
-            .features
-            .iter()
-            .filter(|f| !f.is_empty())
-            .map(String::as_str)
-            .collect();
-        let mut visited: HashSet<&str> = HashSet::new();
-        while let Some(feature) = to_resolve.pop() {
-            if feature == name {
-                return true;
-            }
-            if visited.contains(feature) {
-                continue;
-            }
-            visited.insert(feature);
-            if features_table.contains_key(feature) {
-                let specified_features = match features_table.get(feature) {
-                    None => panic!("Feature {feature} does not exist"),
-                    Some(Value::Array(specified_features)) => specified_features,
-                    _ => panic!("Feature {feature} is not mapped to an array"),
-                };
-                for spec in specified_features {
-                    if let Value::String(ref spec) = *spec {
-                        to_resolve.push(spec.as_str());
-                    }
-                }
-            }
+    /// Whether the dependency `name` of workspace member `package_name` is
+    /// activated by the requested features
+    fn feature_includes(
+        &self,
+        name: &str,
+        optional: bool,
+        features_table: &Option<Value>,
+        workspace: &ElaborateWorkspace<'_>,
+        package_name: &str,
+    ) -> CargoResult<bool> {
+        if !optional || self.options.all_features {
+            return Ok(true);
         }
-        false
+        let members: Vec<_> = workspace.workspace.members().map(|m| m.name()).collect();
+        let members: Vec<&str> = members.iter().map(|m| m.as_str()).collect();
+        let requested = self.options.member_features(package_name, &members);
+        activates_dependency(features_table, &requested, name)
     }
 
     fn update_version_and_feature(
@@ -520,6 +799,7 @@ impl<'tmp> TempProject<'tmp> {
         workspace: &ElaborateWorkspace<'_>,
         package_name: &str,
         version_to_latest: bool,
+        held_back: &HashSet<String>,
     ) -> CargoResult<()> {
         let dep_keys: Vec<_> = dependencies.keys().cloned().collect();
         for dep_key in dep_keys {
@@ -528,7 +808,7 @@ impl<'tmp> TempProject<'tmp> {
             // In short this allows cargo to build the package with semver minor
             // compatibilities issues https://github.com/rust-lang/cargo/issues/6584
             // https://github.com/kbknapp/cargo-outdated/issues/230
-            if self.options.exclude.contains(&dep_key) {
+            if self.is_excluded(&dep_key, dependencies.get(&dep_key)) {
                 continue;
             }
 
@@ -540,7 +820,7 @@ impl<'tmp> TempProject<'tmp> {
             match original {
                 Value::String(requirement) => {
                     let name = dep_key;
-                    if version_to_latest {
+                    if version_to_latest && !held_back.contains(&name) {
                         match self.find_update(
                             &name,
                             package_name,
@@ -548,13 +828,18 @@ impl<'tmp> TempProject<'tmp> {
                             workspace,
                             version_to_latest,
                         ) {
-                            Result::Ok(val) => dependencies
-                                .insert(name.clone(), Value::String(val.version().to_string())),
+                            Result::Ok(val) => {
+                                self.record_rewrite(&name, Some(&requirement), val.version());
+                                dependencies
+                                    .insert(name.clone(), Value::String(val.version().to_string()))
+                            }
                             Result::Err(_err) => {
-                                eprintln!(
-                                    "Updates to dependency {} could not be found",
-                                    name.clone()
-                                );
+                                if !self.silent {
+                                    eprintln!(
+                                        "Updates to dependency {} could not be found",
+                                        name.clone()
+                                    );
+                                }
                                 None
                             }
                         };
@@ -572,6 +857,12 @@ impl<'tmp> TempProject<'tmp> {
                         orig_name = name;
                         name = &dep_key;
                     }
+                    let package = if orig_name.is_empty() {
+                        name
+                    } else {
+                        orig_name
+                    };
+                    let version_to_latest = version_to_latest && !held_back.contains(package);
 
                     if !(version_to_latest || t.contains_key("features")) {
                         continue;
@@ -586,7 +877,7 @@ impl<'tmp> TempProject<'tmp> {
                             }
                         })
                         .unwrap_or(false);
-                    if !self.feature_includes(name, optional, features) {
+                    if !self.feature_includes(name, optional, features, workspace, package_name)? {
                         continue;
                     }
                     let mut replaced = t.clone();
@@ -596,11 +887,7 @@ impl<'tmp> TempProject<'tmp> {
                         _ => None,
                     };
                     let r_summary = self.find_update(
-                        if orig_name.is_empty() {
-                            name
-                        } else {
-                            orig_name
-                        },
+                        package,
                         package_name,
                         requirement,
                         workspace,
@@ -609,11 +896,14 @@ impl<'tmp> TempProject<'tmp> {
                     let summary = match r_summary {
                         Result::Ok(val) => val,
                         Result::Err(_) => {
-                            eprintln!("Update for {} could not be found!", name.clone());
+                            if !self.silent {
+                                eprintln!("Update for {} could not be found!", name.clone());
+                            }
                             return Ok(());
                         }
                     };
                     if version_to_latest && t.contains_key("version") {
+                        self.record_rewrite(package, requirement, summary.version());
                         replaced.insert(
                             "version".to_owned(),
                             Value::String(summary.version().to_string()),
@@ -725,7 +1015,33 @@ impl<'tmp> TempProject<'tmp> {
         Ok(())
     }
 
+    /// Whether a dependency matches `--exclude` by either its key or, if
+    /// renamed, its package name
+    fn is_excluded(&self, dep_key: &str, spec: Option<&Value>) -> bool {
+        if self.exclude.matches(dep_key) {
+            return true;
+        }
+        matches!(
+            spec.and_then(|spec| spec.get("package")),
+            Some(Value::String(package)) if self.exclude.matches(package)
+        )
+    }
+
+    /// Record a dependency rewritten to a version its requirement didn't
+    /// allow
+    fn record_rewrite(&self, name: &str, requirement: Option<&str>, version: &Version) {
+        let allowed = requirement
+            .and_then(|r| VersionReq::parse(r).ok())
+            .is_some_and(|r| r.matches(version));
+        if !allowed {
+            self.rewritten.borrow_mut().insert(name.to_owned());
+        }
+    }
+
     fn warn<T: ::std::fmt::Display>(&self, message: T) -> CargoResult<()> {
+        if self.silent {
+            return Ok(());
+        }
         let original_verbosity = self.context.shell().verbosity();
         self.context.shell().set_verbosity(if self.options.quiet {
             Verbosity::Quiet
@@ -738,6 +1054,33 @@ impl<'tmp> TempProject<'tmp> {
     }
 }
 
+/// Whether a temporary project resolves once `write` has written its
+/// manifests, passing errors other than resolver conflicts through
+pub fn resolves_with<F>(
+    orig_workspace: &ElaborateWorkspace<'_>,
+    orig_manifest: &str,
+    options: &Options,
+    write: F,
+) -> CargoResult<bool>
+where
+    F: for<'a> FnOnce(&'a TempProject<'a>) -> CargoResult<()>,
+{
+    let mut proj = TempProject::from_workspace(orig_workspace, orig_manifest, options)?;
+    proj.silent = true;
+    write(&proj)?;
+    match proj.cargo_update() {
+        Ok(()) => Ok(true),
+        Err(error) if is_resolve_conflict(&error) => Ok(false),
+        Err(error) => Err(error),
+    }
+}
+
+/// Whether `error` comes from the resolver failing to select versions
+/// rather than e.g. failing to reach the registry
+fn is_resolve_conflict(error: &anyhow::Error) -> bool {
+    error.downcast_ref::<ResolveError>().is_some()
+}
+
 /// Features and optional dependencies of a Summary
 fn features_and_options(summary: &Summary) -> HashSet<&str> {
     let mut result: HashSet<&str> = summary.features().keys().map(|s| s.as_str()).collect();
@@ -816,6 +1159,119 @@ fn manifest_paths(elab: &ElaborateWorkspace<'_>) -> CargoResult<Vec<PathBuf>> {
     Ok(manifest_paths)
 }
 
+/// Name of the package of the dependency declared as `dep_key`
+fn dependency_package(dep_key: &str, dependency: &Value) -> String {
+    match dependency.get("package") {
+        Some(Value::String(package)) => package.clone(),
+        _ => dep_key.to_owned(),
+    }
+}
+
+/// The version requirement of a dependency, if it has one
+fn requirement_mut(dependency: &mut Value) -> Option<&mut String> {
+    match dependency {
+        Value::String(requirement) => Some(requirement),
+        Value::Table(t) => match t.get_mut("version") {
+            Some(Value::String(requirement)) => Some(requirement),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Pin the dependencies of a table declared by `member` to the version
+/// `lowest` finds for their requirement, returning the pins. Dependencies
+/// inherited from the workspace are recorded in `inherited` instead, to be
+/// pinned by `pin_inherited`.
+fn pin_dependencies<F>(
+    dependencies: &mut Table,
+    member: &str,
+    inherited: &mut BTreeMap<String, BTreeSet<String>>,
+    lowest: &mut F,
+) -> CargoResult<Vec<MinimalPin>>
+where
+    F: FnMut(&str, &str, &Value, &str) -> CargoResult<Option<Version>>,
+{
+    let members = BTreeSet::from([member.to_owned()]);
+    let mut pins = vec![];
+    for (dep_key, dependency) in dependencies.iter_mut() {
+        if dependency.get("workspace").and_then(Value::as_bool) == Some(true) {
+            inherited
+                .entry(dep_key.clone())
+                .or_default()
+                .insert(member.to_owned());
+        } else {
+            pins.extend(pin_dependency(dep_key, dependency, &members, lowest)?);
+        }
+    }
+    Ok(pins)
+}
+
+/// Pin the `[workspace.dependencies]` of the root manifest `root` which are
+/// `inherited` by members, as recorded by `pin_dependencies`
+fn pin_inherited<F>(
+    root: &mut Table,
+    inherited: &BTreeMap<String, BTreeSet<String>>,
+    lowest: &mut F,
+) -> CargoResult<Vec<MinimalPin>>
+where
+    F: FnMut(&str, &str, &Value, &str) -> CargoResult<Option<Version>>,
+{
+    let mut pins = vec![];
+    if let Some(Value::Table(deps)) = root
+        .get_mut("workspace")
+        .and_then(|workspace| workspace.get_mut("dependencies"))
+    {
+        for (dep_key, dependency) in deps.iter_mut() {
+            if let Some(members) = inherited.get(dep_key) {
+                pins.extend(pin_dependency(dep_key, dependency, members, lowest)?);
+            }
+        }
+    }
+    Ok(pins)
+}
+
+/// Pin `dependency`, declared as `dep_key` for `members`, to the version
+/// `lowest` finds for its requirement, returning a pin for each member
+fn pin_dependency<F>(
+    dep_key: &str,
+    dependency: &mut Value,
+    members: &BTreeSet<String>,
+    lowest: &mut F,
+) -> CargoResult<Vec<MinimalPin>>
+where
+    F: FnMut(&str, &str, &Value, &str) -> CargoResult<Option<Version>>,
+{
+    let (Some(member), Some(requirement)) = (members.first(), requirement_mut(dependency).cloned())
+    else {
+        return Ok(vec![]);
+    };
+    let Some(version) = lowest(member, dep_key, dependency, &requirement)? else {
+        return Ok(vec![]);
+    };
+    if let Some(pinned) = requirement_mut(dependency) {
+        *pinned = format!("={version}");
+    }
+    let package = dependency_package(dep_key, dependency);
+    Ok(members
+        .iter()
+        .map(|member| MinimalPin {
+            member: member.clone(),
+            name: package.clone(),
+            requirement: requirement.clone(),
+            lowest: version.to_string(),
+        })
+        .collect())
+}
+
+/// The lowest of `versions` matching `req`
+fn lowest_matching<'a>(
+    req: &VersionReq,
+    versions: impl IntoIterator<Item = &'a Version>,
+) -> Option<&'a Version> {
+    versions.into_iter().filter(|v| req.matches(v)).min()
+}
+
 fn valid_latest_version(mut requirement: &str, version: &Version) -> bool {
     match (requirement.contains('-'), !version.pre.is_empty()) {
         // if user was on a stable channel, it's unlikely for him to update to an unstable one
@@ -844,3 +1300,191 @@ fn valid_latest_version(mut requirement: &str, version: &Version) -> bool {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    fn conflict() -> anyhow::Error {
+        ResolveError::new(anyhow!("failed to select a version"), vec![]).into()
+    }
+
+    /// Bisect the upgrades `a` to `e` with an oracle failing whenever all
+    /// the upgrades of any of `conflicts` are kept
+    fn bisect(conflicts: &[&[&str]]) -> CargoResult<BTreeMap<String, String>> {
+        let upgrades = ["a", "b", "c", "d", "e"].map(str::to_owned).to_vec();
+        TempProject::bisect_unresolvable(upgrades, conflict(), |held_back| {
+            Ok(conflicts
+                .iter()
+                .all(|conflict| conflict.iter().any(|&u| held_back.contains(u))))
+        })
+    }
+
+    fn expected(culprits: &[(&str, &str)]) -> BTreeMap<String, String> {
+        culprits
+            .iter()
+            .map(|&(culprit, conflict)| (culprit.to_owned(), conflict.to_owned()))
+            .collect()
+    }
+
+    #[test]
+    fn bisect_one_unresolvable() {
+        assert_eq!(
+            expected(&[("c", "the current dependencies")]),
+            bisect(&[&["c"]]).unwrap()
+        );
+        assert_eq!(expected(&[("d", "b")]), bisect(&[&["b", "d"]]).unwrap());
+    }
+
+    #[test]
+    fn bisect_two_unresolvable() {
+        assert_eq!(
+            expected(&[
+                ("b", "the current dependencies"),
+                ("e", "the current dependencies")
+            ]),
+            bisect(&[&["b"], &["e"]]).unwrap()
+        );
+    }
+
+    #[test]
+    fn bisect_none_unresolvable() {
+        assert_eq!(expected(&[]), bisect(&[]).unwrap());
+    }
+
+    #[test]
+    fn bisect_other_errors() {
+        let upgrades = vec!["a".to_owned()];
+        let error =
+            TempProject::bisect_unresolvable(upgrades.clone(), anyhow!("offline"), |_| Ok(true))
+                .unwrap_err();
+        assert_eq!("offline", error.to_string());
+
+        let error = TempProject::bisect_unresolvable(upgrades, conflict(), |_| {
+            Err(anyhow!("network unreachable"))
+        })
+        .unwrap_err();
+        assert_eq!("network unreachable", error.to_string());
+
+        // the upgrades are not blamed when holding them all back fails too
+        assert!(bisect(&[&[]]).is_err());
+    }
+
+    #[test]
+    fn lowest_matching_version() {
+        let versions: Vec<_> = ["0.3.9", "0.4.0-alpha", "0.4.2", "0.4.0", "0.5.0"]
+            .iter()
+            .map(|v| Version::parse(v).unwrap())
+            .collect();
+        let lowest = |req: &str| {
+            lowest_matching(&VersionReq::parse(req).unwrap(), &versions).map(Version::to_string)
+        };
+
+        assert_eq!(Some("0.4.0".to_owned()), lowest("0.4"));
+        assert_eq!(Some("0.4.2".to_owned()), lowest("^0.4.1"));
+        assert_eq!(Some("0.3.9".to_owned()), lowest(">=0.3, <0.5"));
+        assert_eq!(None, lowest("1"));
+    }
+
+    /// Lowest versions of a stub registry, `held` being held back
+    fn stub_lowest(
+        _member: &str,
+        dep_key: &str,
+        dependency: &Value,
+        requirement: &str,
+    ) -> CargoResult<Option<Version>> {
+        let versions: Vec<_> = ["0.4.0", "0.4.8", "1.0.0", "1.0.100", "1.0.200"]
+            .iter()
+            .map(|v| Version::parse(v).unwrap())
+            .collect();
+        if dependency_package(dep_key, dependency) == "held" {
+            return Ok(None);
+        }
+        Ok(lowest_matching(&VersionReq::parse(requirement).unwrap(), &versions).cloned())
+    }
+
+    fn pin(member: &str, name: &str, requirement: &str, lowest: &str) -> MinimalPin {
+        MinimalPin {
+            member: member.to_owned(),
+            name: name.to_owned(),
+            requirement: requirement.to_owned(),
+            lowest: lowest.to_owned(),
+        }
+    }
+
+    #[test]
+    fn pin_declared() {
+        let mut deps: Table = ::toml::from_str(
+            r#"
+            log = "0.4"
+            json = { version = "1.0.100", package = "serde_json" }
+            held = "1"
+            local = { path = "../local" }
+            "#,
+        )
+        .unwrap();
+        let mut inherited = BTreeMap::new();
+        let mut pins =
+            pin_dependencies(&mut deps, "app", &mut inherited, &mut stub_lowest).unwrap();
+        pins.sort();
+
+        assert_eq!(
+            vec![
+                pin("app", "log", "0.4", "0.4.0"),
+                pin("app", "serde_json", "1.0.100", "1.0.100"),
+            ],
+            pins
+        );
+        assert_eq!("=0.4.0", deps["log"].as_str().unwrap());
+        assert_eq!("=1.0.100", deps["json"]["version"].as_str().unwrap());
+        assert_eq!("1", deps["held"].as_str().unwrap());
+        assert!(inherited.is_empty());
+    }
+
+    #[test]
+    fn pin_inherited_dependencies() {
+        let mut root: Table = ::toml::from_str(
+            r#"
+            [workspace]
+            members = ["app", "cli"]
+
+            [workspace.dependencies]
+            serde = { version = "1.0.100", features = ["derive"] }
+            log = "0.4"
+            "#,
+        )
+        .unwrap();
+        let mut app: Table = ::toml::from_str(r#"serde = { workspace = true }"#).unwrap();
+        let mut cli: Table =
+            ::toml::from_str(r#"serde = { workspace = true, optional = true }"#).unwrap();
+
+        let mut inherited = BTreeMap::new();
+        let mut pins = pin_dependencies(&mut app, "app", &mut inherited, &mut stub_lowest).unwrap();
+        pins.extend(pin_dependencies(&mut cli, "cli", &mut inherited, &mut stub_lowest).unwrap());
+        assert!(pins.is_empty());
+        assert_eq!(
+            BTreeMap::from([(
+                "serde".to_owned(),
+                BTreeSet::from(["app".to_owned(), "cli".to_owned()])
+            )]),
+            inherited
+        );
+
+        let pins = pin_inherited(&mut root, &inherited, &mut stub_lowest).unwrap();
+        assert_eq!(
+            vec![
+                pin("app", "serde", "1.0.100", "1.0.100"),
+                pin("cli", "serde", "1.0.100", "1.0.100"),
+            ],
+            pins
+        );
+        let deps = &root["workspace"]["dependencies"];
+        assert_eq!("=1.0.100", deps["serde"]["version"].as_str().unwrap());
+        // not inherited by any checked member
+        assert_eq!("0.4", deps["log"].as_str().unwrap());
+        // the members still inherit the requirement
+        assert_eq!(Some(true), app["serde"]["workspace"].as_bool());
+    }
+}
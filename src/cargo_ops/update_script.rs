@@ -0,0 +1,135 @@
+use std::io::{self, Write};
+
+use anyhow::Context;
+use cargo::{
+    core::{PackageId, Workspace},
+    ops::{UpdateOptions, update_lockfile},
+    util::CargoResult,
+};
+use semver::Version;
+use serde::Serialize;
+
+use super::report::print_json_line;
+
+/// A lock file update of a package to a compatible version
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct PreciseUpdate {
+    /// Package id spec selecting the package unambiguously
+    pub spec: String,
+    pub from: String,
+    pub precise: String,
+    pub command: String,
+}
+
+impl PreciseUpdate {
+    /// The update of `id` to `precise`, where `resolved` are all the
+    /// packages of the lock file
+    pub fn new(id: PackageId, precise: &Version, resolved: &[PackageId]) -> PreciseUpdate {
+        let spec = package_spec(id, resolved);
+        PreciseUpdate {
+            command: format!("cargo update -p {} --precise {}", quote(&spec), precise),
+            spec,
+            from: id.version().to_string(),
+            precise: precise.to_string(),
+        }
+    }
+
+    /// Update the lock file of `workspace` accordingly
+    pub fn apply(&self, workspace: &Workspace<'_>) -> CargoResult<()> {
+        let update_opts = UpdateOptions {
+            recursive: false,
+            precise: Some(&self.precise),
+            to_update: vec![self.spec.clone()],
+            gctx: workspace.gctx(),
+            dry_run: false,
+            workspace: false,
+        };
+        update_lockfile(workspace, &update_opts)
+            .with_context(|| format!("cannot update {} to {}", self.spec, self.precise))
+    }
+}
+
+/// The shortest package id spec matching `id` alone among `resolved`
+fn package_spec(id: PackageId, resolved: &[PackageId]) -> String {
+    let same_name = resolved.iter().filter(|other| other.name() == id.name());
+    if same_name.clone().count() <= 1 {
+        id.name().to_string()
+    } else if same_name
+        .filter(|other| other.version() == id.version())
+        .count()
+        <= 1
+    {
+        format!("{}@{}", id.name(), id.version())
+    } else {
+        id.to_spec().to_string()
+    }
+}
+
+/// Quote `word` for a POSIX shell, unless it is safe as is
+fn quote(word: &str) -> String {
+    if word
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || "-_.@".contains(c))
+    {
+        word.to_owned()
+    } else {
+        format!("'{}'", word.replace('\'', r"'\''"))
+    }
+}
+
+/// Print the updates as a shell script
+pub fn print_update_script(updates: &[PreciseUpdate]) -> CargoResult<()> {
+    let mut stdout = io::stdout().lock();
+    writeln!(stdout, "#!/bin/sh")?;
+    writeln!(
+        stdout,
+        "# Compatible lock file updates, to run from the workspace root"
+    )?;
+    writeln!(stdout, "set -e")?;
+    for update in updates {
+        writeln!(stdout, "{}", update.command)?;
+    }
+    stdout.flush()?;
+    Ok(())
+}
+
+/// Print the updates as a JSON line
+pub fn print_update_script_json(
+    updates: &[PreciseUpdate],
+    workspace_root: Option<&str>,
+) -> CargoResult<()> {
+    print_json_line("updates", updates, workspace_root)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use cargo::core::SourceId;
+    use pretty_assertions::assert_eq;
+
+    fn id(name: &str, version: &str, source: &str) -> PackageId {
+        PackageId::try_new(name, version, SourceId::from_url(source).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn specs() {
+        let crates_io = "registry+https://github.com/rust-lang/crates.io-index";
+        let mirror = "registry+https://example.com/index";
+        let resolved = [
+            id("log", "0.4.8", crates_io),
+            id("rand", "0.7.3", crates_io),
+            id("rand", "0.8.5", crates_io),
+            id("syn", "2.0.72", crates_io),
+            id("syn", "2.0.72", mirror),
+        ];
+        let update = |i: usize| PreciseUpdate::new(resolved[i], &Version::new(9, 9, 9), &resolved);
+
+        assert_eq!("cargo update -p log --precise 9.9.9", update(0).command);
+        assert_eq!("rand@0.7.3", update(1).spec);
+        assert_eq!(
+            "cargo update -p 'registry+https://example.com/index#syn@2.0.72' --precise 9.9.9",
+            update(4).command
+        );
+    }
+}
@@ -0,0 +1,327 @@
+use std::{
+    collections::{BTreeMap, btree_map::Entry},
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::anyhow;
+use cargo::{core::dependency::DepKind, util::CargoResult};
+use semver::Version;
+use toml_edit::{DocumentMut, Item, Value};
+
+/// Which version a direct dependency should be upgraded to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Selection {
+    Keep,
+    Compat,
+    Latest,
+}
+
+impl fmt::Display for Selection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Selection::Keep => write!(f, "---"),
+            Selection::Compat => write!(f, "compat"),
+            Selection::Latest => write!(f, "latest"),
+        }
+    }
+}
+
+/// An outdated direct dependency of a workspace member
+#[derive(Debug, Clone)]
+pub struct Upgrade {
+    /// Name of the member declaring the dependency
+    pub member: String,
+    /// Manifest of the member declaring the dependency
+    pub manifest_path: PathBuf,
+    /// Name of the dependency as written in the manifest
+    pub name: String,
+    /// Name of the package of the dependency
+    pub package: String,
+    pub kind: DepKind,
+    pub platform: Option<String>,
+    pub project: Version,
+    pub compat: Option<Version>,
+    pub latest: Option<Version>,
+    pub selection: Selection,
+}
+
+impl Upgrade {
+    /// The version to upgrade to according to the selection, if any
+    pub fn selected_version(&self) -> Option<&Version> {
+        match self.selection {
+            Selection::Keep => None,
+            Selection::Compat => self.compat.as_ref(),
+            Selection::Latest => self.latest.as_ref(),
+        }
+    }
+}
+
+/// A manifest along with its original and rewritten contents
+pub struct ManifestChange {
+    pub path: PathBuf,
+    pub original: String,
+    pub updated: String,
+}
+
+impl ManifestChange {
+    /// A line based diff of the change
+    pub fn diff(&self) -> String {
+        let mut diff = format!(
+            "--- {path}\n+++ {path}\n",
+            path = self.path.to_string_lossy()
+        );
+        let original: Vec<_> = self.original.lines().collect();
+        let updated: Vec<_> = self.updated.lines().collect();
+        if original.len() != updated.len() {
+            original
+                .iter()
+                .for_each(|l| diff.push_str(&format!("-{l}\n")));
+            updated
+                .iter()
+                .for_each(|l| diff.push_str(&format!("+{l}\n")));
+            return diff;
+        }
+        for (number, (o, u)) in original.iter().zip(&updated).enumerate() {
+            if o != u {
+                diff.push_str(&format!("@@ line {} @@\n-{}\n+{}\n", number + 1, o, u));
+            }
+        }
+        diff
+    }
+
+    /// Write the rewritten contents to disk
+    pub fn apply(&self) -> CargoResult<()> {
+        fs::write(&self.path, &self.updated)?;
+        Ok(())
+    }
+}
+
+/// Rewrite requirements of the selected upgrades, returning the manifests
+/// that would change. Dependencies inherited with `workspace = true` are
+/// rewritten in `[workspace.dependencies]` of `workspace_manifest`.
+pub fn manifest_changes(
+    upgrades: &[Upgrade],
+    workspace_manifest: &Path,
+) -> CargoResult<Vec<ManifestChange>> {
+    let mut documents: BTreeMap<PathBuf, (String, DocumentMut)> = BTreeMap::new();
+    fn load<'d>(
+        documents: &'d mut BTreeMap<PathBuf, (String, DocumentMut)>,
+        path: &Path,
+    ) -> CargoResult<&'d mut DocumentMut> {
+        let (_, document) = match documents.entry(path.to_owned()) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let original = fs::read_to_string(path)?;
+                let document = original.parse::<DocumentMut>()?;
+                entry.insert((original, document))
+            }
+        };
+        Ok(document)
+    }
+
+    let mut inherited = vec![];
+    for upgrade in upgrades {
+        let Some(version) = upgrade.selected_version() else {
+            continue;
+        };
+        let document = load(&mut documents, &upgrade.manifest_path)?;
+        let table = dependency_table(document, upgrade.kind, upgrade.platform.as_deref());
+        let item = table
+            .and_then(|t| t.get_mut(&upgrade.name))
+            .ok_or_else(|| {
+                anyhow!(
+                    "Dependency {} not found in {}",
+                    upgrade.name,
+                    upgrade.manifest_path.to_string_lossy()
+                )
+            })?;
+        if is_inherited(item) {
+            inherited.push((upgrade, version));
+        } else {
+            set_requirement(item, version);
+        }
+    }
+
+    if !inherited.is_empty() {
+        let document = load(&mut documents, workspace_manifest)?;
+        for (upgrade, version) in inherited {
+            let item = document
+                .get_mut("workspace")
+                .and_then(|w| w.get_mut("dependencies"))
+                .and_then(|d| d.get_mut(&upgrade.name))
+                .ok_or_else(|| {
+                    anyhow!(
+                        "Dependency {} not found in [workspace.dependencies]",
+                        upgrade.name
+                    )
+                })?;
+            set_requirement(item, version);
+        }
+    }
+
+    Ok(documents
+        .into_iter()
+        .map(|(path, (original, document))| ManifestChange {
+            path,
+            original,
+            updated: document.to_string(),
+        })
+        .filter(|change| change.original != change.updated)
+        .collect())
+}
+
+fn dependency_table<'d>(
+    document: &'d mut DocumentMut,
+    kind: DepKind,
+    platform: Option<&str>,
+) -> Option<&'d mut Item> {
+    let kind = match kind {
+        DepKind::Normal => "dependencies",
+        DepKind::Development => "dev-dependencies",
+        DepKind::Build => "build-dependencies",
+    };
+    match platform {
+        Some(platform) => document
+            .get_mut("target")
+            .and_then(|t| t.get_mut(platform))
+            .and_then(|t| t.get_mut(kind)),
+        None => document.get_mut(kind),
+    }
+}
+
+fn is_inherited(item: &Item) -> bool {
+    item.get("workspace")
+        .and_then(Item::as_bool)
+        .unwrap_or(false)
+}
+
+/// Replace the version requirement of a dependency item, keeping its
+/// formatting
+fn set_requirement(item: &mut Item, version: &Version) {
+    let value = match item {
+        Item::Value(Value::String(_)) => item.as_value_mut(),
+        _ => item.get_mut("version").and_then(Item::as_value_mut),
+    };
+    if let Some(value) = value
+        && let Some(requirement) = value.as_str()
+    {
+        let decor = value.decor().clone();
+        *value = Value::from(bump_requirement(requirement, version));
+        *value.decor_mut() = decor;
+    }
+}
+
+/// A requirement on `version` using the same operator as `requirement` when
+/// it is a single caret, tilde or exact comparator
+pub fn bump_requirement(requirement: &str, version: &Version) -> String {
+    let requirement = requirement.trim();
+    let operator = match requirement.chars().next() {
+        Some(op @ ('^' | '~' | '=')) if !requirement.contains(',') => op.to_string(),
+        _ => String::new(),
+    };
+    format!("{operator}{version}")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    const MANIFEST: &str = r#"[package]
+name = "member-a"
+version = "0.1.0"
+
+[dependencies]
+# pinned for reasons
+log = "0.4.8" # trailing
+rand = { version = "~0.7", features = ["small_rng"] }
+serde = { workspace = true }
+
+[target.'cfg(unix)'.build-dependencies]
+cc = "=1.0.50"
+"#;
+
+    const WORKSPACE: &str = r#"[workspace]
+members = ["member-a"]
+
+[workspace.dependencies]
+serde = "1.0.100"
+"#;
+
+    fn upgrade(
+        name: &str,
+        kind: DepKind,
+        platform: Option<&str>,
+        manifest_path: &Path,
+        selection: Selection,
+    ) -> Upgrade {
+        Upgrade {
+            member: "member-a".to_owned(),
+            manifest_path: manifest_path.to_owned(),
+            name: name.to_owned(),
+            package: name.to_owned(),
+            kind,
+            platform: platform.map(ToOwned::to_owned),
+            project: Version::new(0, 0, 1),
+            compat: Some(Version::new(1, 1, 1)),
+            latest: Some(Version::new(2, 0, 0)),
+            selection,
+        }
+    }
+
+    #[test]
+    fn bump() {
+        let version = Version::new(1, 2, 3);
+        assert_eq!("1.2.3", bump_requirement("0.7", &version));
+        assert_eq!("^1.2.3", bump_requirement("^0.7", &version));
+        assert_eq!("~1.2.3", bump_requirement("~0.7", &version));
+        assert_eq!("=1.2.3", bump_requirement("=0.7.1", &version));
+        assert_eq!("1.2.3", bump_requirement(">=0.7, <0.9", &version));
+        assert_eq!("1.2.3", bump_requirement("*", &version));
+    }
+
+    #[test]
+    fn rewrite() {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest = dir.path().join("member-a").join("Cargo.toml");
+        let workspace = dir.path().join("Cargo.toml");
+        fs::create_dir_all(manifest.parent().unwrap()).unwrap();
+        fs::write(&manifest, MANIFEST).unwrap();
+        fs::write(&workspace, WORKSPACE).unwrap();
+
+        let upgrades = vec![
+            upgrade("log", DepKind::Normal, None, &manifest, Selection::Compat),
+            upgrade("rand", DepKind::Normal, None, &manifest, Selection::Latest),
+            upgrade("serde", DepKind::Normal, None, &manifest, Selection::Latest),
+            upgrade(
+                "cc",
+                DepKind::Build,
+                Some("cfg(unix)"),
+                &manifest,
+                Selection::Keep,
+            ),
+        ];
+        let changes = manifest_changes(&upgrades, &workspace).unwrap();
+
+        assert_eq!(2, changes.len());
+        let member = changes.iter().find(|c| c.path == manifest).unwrap();
+        assert_eq!(
+            MANIFEST
+                .replace(r#""0.4.8""#, r#""1.1.1""#)
+                .replace(r#""~0.7""#, r#""~2.0.0""#),
+            member.updated
+        );
+        let root = changes.iter().find(|c| c.path == workspace).unwrap();
+        assert_eq!(
+            WORKSPACE.replace(r#""1.0.100""#, r#""2.0.0""#),
+            root.updated
+        );
+        assert!(
+            member
+                .diff()
+                .contains("@@ line 7 @@\n-log = \"0.4.8\" # trailing\n")
+        );
+    }
+}
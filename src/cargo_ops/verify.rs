@@ -0,0 +1,411 @@
+use std::{
+    env,
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::anyhow;
+use cargo::util::{CargoResult, context::GlobalContext};
+use serde::Serialize;
+use tempfile::Builder;
+use toml_edit::{DocumentMut, Item, value};
+
+use super::{
+    plan::Batch,
+    report::{print_json_line, print_section},
+    upgrade::{Selection, Upgrade, manifest_changes},
+};
+use crate::{Options, cli::Verify};
+
+/// The outcome of building the workspace with a batch of upgrades
+#[derive(Debug, Serialize)]
+pub struct Verification {
+    /// Position of the batch in the upgrade plan, starting from 1
+    pub batch: usize,
+    pub upgrades: Vec<String>,
+    pub drop_in: bool,
+    /// The first error reported by Cargo when the build breaks
+    pub error: Option<String>,
+}
+
+/// Build a copy of the workspace rooted at `workspace_root` with each batch
+/// of upgrades applied in turn, running `cargo check` or `cargo test` as
+/// requested by `--verify`
+pub fn verify_batches(
+    workspace_root: &Path,
+    relative_manifest: &Path,
+    batches: &[Batch],
+    upgrades: &[Upgrade],
+    options: &Options,
+    context: &GlobalContext,
+) -> CargoResult<Vec<Verification>> {
+    let Some(verify) = options.verify else {
+        return Ok(vec![]);
+    };
+    // shared by every build so that unchanged dependencies build once
+    let target_dir = Builder::new().prefix("cargo-outdated-target").tempdir()?;
+    let build = |root: &Path| {
+        run_cargo(
+            verify,
+            &root.join(relative_manifest),
+            target_dir.path(),
+            options,
+        )
+    };
+
+    verbose!(context, "Verifying...", "the project without upgrades");
+    let baseline = Builder::new().prefix("cargo-outdated").tempdir()?;
+    copy_sources(workspace_root, baseline.path())?;
+    if let Err(error) = build(baseline.path()) {
+        return Err(anyhow!(
+            "cannot verify upgrades, the project does not build without them: {error}"
+        ));
+    }
+
+    let mut verifications = vec![];
+    for (i, batch) in batches.iter().enumerate() {
+        let names: Vec<_> = batch
+            .upgrades
+            .iter()
+            .map(|u| format!("{} {}", u.name, u.target))
+            .collect();
+        verbose!(context, "Verifying...", names.join(", "));
+        let copy = Builder::new().prefix("cargo-outdated").tempdir()?;
+        copy_sources(workspace_root, copy.path())?;
+        let batch_upgrades = upgrades
+            .iter()
+            .filter(|u| batch.upgrades.iter().any(|p| p.name == u.package))
+            .map(|u| {
+                let relative = u.manifest_path.strip_prefix(workspace_root).map_err(|_| {
+                    anyhow!(
+                        "cannot verify upgrades of {}, its manifest is outside of the workspace",
+                        u.member
+                    )
+                })?;
+                Ok(Upgrade {
+                    manifest_path: copy.path().join(relative),
+                    selection: if u.latest.is_some() {
+                        Selection::Latest
+                    } else {
+                        Selection::Compat
+                    },
+                    ..u.clone()
+                })
+            })
+            .collect::<CargoResult<Vec<_>>>()?;
+        for change in manifest_changes(&batch_upgrades, &copy.path().join("Cargo.toml"))? {
+            change.apply()?;
+        }
+        let error = build(copy.path()).err();
+        verifications.push(Verification {
+            batch: i + 1,
+            upgrades: names,
+            drop_in: error.is_none(),
+            error,
+        });
+    }
+    Ok(verifications)
+}
+
+/// Run Cargo against `manifest`, returning the first error it reports if it
+/// fails
+fn run_cargo(
+    verify: Verify,
+    manifest: &Path,
+    target_dir: &Path,
+    options: &Options,
+) -> Result<(), String> {
+    let cargo = env::var_os("CARGO").unwrap_or_else(|| OsString::from("cargo"));
+    let mut command = Command::new(cargo);
+    command
+        .arg(verify.to_string())
+        .arg("--manifest-path")
+        .arg(manifest)
+        .args(["--workspace", "--quiet", "--color", "never"])
+        .args(["--message-format", "short"])
+        .env("CARGO_TARGET_DIR", target_dir);
+    if verify == Verify::Check {
+        command.arg("--all-targets");
+    }
+    if options.offline || options.frozen {
+        command.arg("--offline");
+    }
+    for config in &options.config {
+        command.arg("--config").arg(config);
+    }
+    for flag in &options.unstable_flags {
+        command.arg("-Z").arg(flag);
+    }
+    let output = command
+        .output()
+        .map_err(|e| format!("cannot run cargo: {e}"))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(first_error(&String::from_utf8_lossy(&output.stderr)))
+    }
+}
+
+/// The first error of the output of Cargo, or the whole output if it
+/// reports none
+fn first_error(stderr: &str) -> String {
+    stderr
+        .lines()
+        .find(|line| line.starts_with("error") || line.contains(": error"))
+        .unwrap_or(stderr.trim())
+        .trim()
+        .to_owned()
+}
+
+/// Copy the sources of the workspace at `workspace_root` to `to`, leaving
+/// out build outputs and version control
+fn copy_sources(workspace_root: &Path, to: &Path) -> CargoResult<()> {
+    copy_dir(&fs::canonicalize(workspace_root)?, workspace_root, to)
+}
+
+/// Copy the directory `from` of the workspace at `workspace_root` to `to`,
+/// copying symbolic links as links and making the relative links and path
+/// dependencies of manifests which point outside the workspace absolute
+fn copy_dir(workspace_root: &Path, from: &Path, to: &Path) -> CargoResult<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        if name == "target" || name == ".git" {
+            continue;
+        }
+        let path = entry.path();
+        let file_type = fs::symlink_metadata(&path)?.file_type();
+        if file_type.is_symlink() {
+            copy_link(workspace_root, &path, &to.join(&name))?;
+        } else if file_type.is_dir() {
+            copy_dir(workspace_root, &path, &to.join(&name))?;
+        } else if name == "Cargo.toml" {
+            let manifest = fs::read_to_string(&path)?;
+            fs::write(
+                to.join(&name),
+                absolute_path_dependencies(&manifest, from, workspace_root)?,
+            )?;
+        } else {
+            fs::copy(&path, to.join(&name))?;
+        }
+    }
+    Ok(())
+}
+
+/// Copy the symbolic link `from` to `to`, making it absolute when it is
+/// relative and points outside the workspace at `workspace_root`
+#[cfg(unix)]
+fn copy_link(workspace_root: &Path, from: &Path, to: &Path) -> CargoResult<()> {
+    let mut target = fs::read_link(from)?;
+    // dangling links are copied as they are
+    if let Ok(absolute) = fs::canonicalize(from)
+        && target.is_relative()
+        && !absolute.starts_with(workspace_root)
+    {
+        target = absolute;
+    }
+    std::os::unix::fs::symlink(target, to)?;
+    Ok(())
+}
+
+/// Links are not copied where creating them may need extra privileges, so
+/// the copy would be incomplete
+#[cfg(not(unix))]
+fn copy_link(_workspace_root: &Path, from: &Path, _to: &Path) -> CargoResult<()> {
+    Err(anyhow!("cannot copy symbolic link {}", from.display()))
+}
+
+/// Rewrite the relative path dependencies of `manifest`, found in
+/// `manifest_dir`, which point outside the workspace at `workspace_root` to
+/// absolute paths, so that they still resolve from a copy of the workspace
+fn absolute_path_dependencies(
+    manifest: &str,
+    manifest_dir: &Path,
+    workspace_root: &Path,
+) -> CargoResult<String> {
+    let mut document = manifest.parse::<DocumentMut>()?;
+    let mut tables = vec![];
+    for (key, item) in document.iter_mut() {
+        match key.get() {
+            "dependencies" | "dev-dependencies" | "build-dependencies" => tables.push(item),
+            // dependency tables of each platform
+            "target" => tables.extend(
+                item.as_table_like_mut()
+                    .into_iter()
+                    .flat_map(|targets| targets.iter_mut())
+                    .filter_map(|(_, target)| target.as_table_like_mut())
+                    .flat_map(|target| target.iter_mut())
+                    .filter(|(key, _)| key.get().ends_with("dependencies"))
+                    .map(|(_, deps)| deps),
+            ),
+            // patches of each source
+            "patch" => tables.extend(
+                item.as_table_like_mut()
+                    .into_iter()
+                    .flat_map(|sources| sources.iter_mut())
+                    .map(|(_, deps)| deps),
+            ),
+            "workspace" => tables.extend(
+                item.as_table_like_mut()
+                    .and_then(|workspace| workspace.get_mut("dependencies")),
+            ),
+            _ => {}
+        }
+    }
+
+    let dependencies = tables
+        .into_iter()
+        .filter_map(Item::as_table_like_mut)
+        .flat_map(|deps| deps.iter_mut())
+        .filter_map(|(_, dependency)| dependency.as_table_like_mut());
+    for dependency in dependencies {
+        let Some(path) = dependency
+            .get("path")
+            .and_then(Item::as_str)
+            .map(PathBuf::from)
+        else {
+            continue;
+        };
+        // missing paths are left for Cargo to report
+        let Ok(absolute) = fs::canonicalize(manifest_dir.join(&path)) else {
+            continue;
+        };
+        if path.is_relative() && !absolute.starts_with(workspace_root) {
+            dependency.insert("path", value(absolute.to_string_lossy().as_ref()));
+        }
+    }
+    Ok(document.to_string())
+}
+
+/// Print the verification section of the report, if any batch is verified
+pub fn print_verifications(verifications: &[Verification]) -> CargoResult<()> {
+    if verifications.is_empty() {
+        return Ok(());
+    }
+    print_section(
+        "Verification",
+        &["Batch", "Upgrades", "Verdict"],
+        verifications.iter().map(|verification| {
+            vec![
+                verification.batch.to_string(),
+                verification.upgrades.join(", "),
+                if verification.drop_in {
+                    "Drop-in"
+                } else {
+                    "Breaks build"
+                }
+                .to_owned(),
+            ]
+        }),
+    )?;
+    for verification in verifications {
+        if let Some(ref error) = verification.error {
+            println!("Batch {}: {}", verification.batch, error);
+        }
+    }
+    Ok(())
+}
+
+/// Print the verified batches as a JSON line, if any
+pub fn print_verifications_json(
+    verifications: &[Verification],
+    workspace_root: Option<&str>,
+) -> CargoResult<()> {
+    if !verifications.is_empty() {
+        print_json_line("verification", verifications, workspace_root)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn first_compiler_error() {
+        let stderr = "warning: unused import: `std::fmt`\n\
+                      src/lib.rs:3:15: error[E0432]: unresolved import `rand::thread_rng`\n\
+                      error: could not compile `a` (lib) due to 1 previous error\n";
+        assert_eq!(
+            "src/lib.rs:3:15: error[E0432]: unresolved import `rand::thread_rng`",
+            first_error(stderr)
+        );
+        assert_eq!("killed", first_error("killed\n"));
+    }
+
+    #[test]
+    fn copied_sources() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("ws");
+        fs::create_dir_all(root.join("inner")).unwrap();
+        fs::create_dir_all(root.join("target/debug")).unwrap();
+        fs::create_dir_all(dir.path().join("outer")).unwrap();
+        fs::write(
+            root.join("Cargo.toml"),
+            "[package]\nname = \"ws\"\n\n\
+             [dependencies]\ninner = { path = \"inner\" }\nouter = { path = \"../outer\" }\n\
+             log = \"0.4\"\n\n\
+             [target.'cfg(unix)'.dev-dependencies]\nouter = { path = \"../outer\" }\n\n\
+             [patch.crates-io.itoa]\npath = \"../outer\"\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("inner/Cargo.toml"),
+            "[package]\nname = \"inner\"\n",
+        )
+        .unwrap();
+        fs::write(root.join("target/debug/ws"), "").unwrap();
+
+        let copy = dir.path().join("copy");
+        copy_sources(&root, &copy).unwrap();
+
+        let outer = fs::canonicalize(dir.path().join("outer")).unwrap();
+        let outer = outer.to_string_lossy();
+        assert_eq!(
+            format!(
+                "[package]\nname = \"ws\"\n\n\
+                 [dependencies]\ninner = {{ path = \"inner\" }}\nouter = {{ path = {outer:?} }}\n\
+                 log = \"0.4\"\n\n\
+                 [target.'cfg(unix)'.dev-dependencies]\nouter = {{ path = {outer:?} }}\n\n\
+                 [patch.crates-io.itoa]\npath = {outer:?}\n"
+            ),
+            fs::read_to_string(copy.join("Cargo.toml")).unwrap()
+        );
+        assert!(copy.join("inner/Cargo.toml").is_file());
+        assert!(!copy.join("target").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn copied_links() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("ws");
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("src/lib.rs"), "").unwrap();
+        std::os::unix::fs::symlink("src", root.join("link")).unwrap();
+        // a link to an ancestor is not followed
+        std::os::unix::fs::symlink("..", root.join("src/parent")).unwrap();
+        // a link outside the workspace would dangle in the copy
+        fs::create_dir_all(dir.path().join("shared")).unwrap();
+        std::os::unix::fs::symlink("../shared", root.join("shared")).unwrap();
+
+        let copy = dir.path().join("copy");
+        copy_sources(&root, &copy).unwrap();
+
+        assert!(copy.join("src/lib.rs").is_file());
+        assert_eq!(Path::new("src"), fs::read_link(copy.join("link")).unwrap());
+        assert_eq!(
+            Path::new(".."),
+            fs::read_link(copy.join("src/parent")).unwrap()
+        );
+        assert_eq!(
+            fs::canonicalize(dir.path().join("shared")).unwrap(),
+            fs::read_link(copy.join("shared")).unwrap()
+        );
+    }
+}
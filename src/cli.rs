@@ -1,6 +1,13 @@
-use std::ffi::OsString;
+use std::{collections::BTreeMap, ffi::OsString};
 
-use clap::{ArgAction, Parser, Subcommand, ValueEnum, error::Result};
+use cargo::core::dependency::DepKind;
+use clap::{
+    ArgAction, CommandFactory, Parser, Subcommand, ValueEnum,
+    error::{ErrorKind, Result},
+};
+use semver::Version;
+
+use crate::pattern::{parse_ignore_rule, parse_pattern};
 
 #[derive(ValueEnum, Copy, Clone, Debug, PartialEq, Default, strum::Display)]
 #[strum(ascii_case_insensitive, serialize_all = "lowercase")]
@@ -19,6 +26,34 @@ pub enum Color {
     Always,
 }
 
+#[derive(ValueEnum, Copy, Clone, Debug, PartialEq, Default, strum::Display)]
+#[strum(ascii_case_insensitive, serialize_all = "lowercase")]
+pub enum GroupBy {
+    /// One table per workspace member
+    #[default]
+    Member,
+    /// One row per outdated dependency, listing the members using it
+    Dependency,
+}
+
+#[derive(ValueEnum, Copy, Clone, Debug, PartialEq, strum::Display)]
+#[strum(ascii_case_insensitive, serialize_all = "lowercase")]
+pub enum Verify {
+    /// Run `cargo check` on all targets
+    Check,
+    /// Run `cargo test`
+    Test,
+}
+
+#[derive(ValueEnum, Copy, Clone, Debug, PartialEq, Eq, Hash, strum::Display)]
+#[strum(ascii_case_insensitive, serialize_all = "lowercase")]
+pub enum Kind {
+    Normal,
+    Build,
+    #[value(alias = "development")]
+    Dev,
+}
+
 #[derive(Parser, Debug)]
 #[clap(bin_name = "cargo")]
 struct Cargo {
@@ -31,49 +66,129 @@ enum CargoCommand {
     Outdated(Options),
 }
 
+#[derive(Subcommand, Debug, Clone, PartialEq)]
+pub enum OutdatedCommand {
+    /// Check binaries installed with `cargo install` for newer versions
+    Installed,
+    /// Stray arguments, reported as unexpected rather than as unknown
+    /// subcommands
+    #[command(external_subcommand)]
+    Unexpected(Vec<String>),
+}
+
 /// Options from CLI arguments
-#[derive(Parser, Debug, PartialEq, Default)]
+#[derive(Parser, Debug, Clone, PartialEq, Default)]
 #[command(version)]
 #[command(about = "Displays information about project dependency versions")]
 pub struct Options {
     /// Output formatting
-    #[arg(long, value_enum, ignore_case = true, default_value_t = Default::default())]
+    #[arg(
+        long,
+        value_enum,
+        ignore_case = true,
+        global = true,
+        default_value_t = Default::default()
+    )]
     pub format: Format,
-    /// Output coloring
+    /// Whether to report outdated dependencies per workspace member or once
+    /// per dependency
     #[arg(long, value_enum, ignore_case = true, default_value_t = Default::default())]
+    pub group_by: GroupBy,
+    /// Output coloring
+    #[arg(
+        long,
+        value_enum,
+        ignore_case = true,
+        global = true,
+        default_value_t = Default::default()
+    )]
     pub color: Color,
-    /// Space-separated list of features
+    /// Space-separated list of features, 'member/feature' only enables the
+    /// feature of that workspace member
     #[arg(long, use_value_delimiter = true)]
     pub features: Vec<String>,
+    /// Activate all available features, as earlier versions did by default
+    /// (only the default features are activated otherwise)
+    #[arg(long)]
+    pub all_features: bool,
+    /// Do not activate the `default` feature
+    #[arg(long)]
+    pub no_default_features: bool,
     /// Dependencies to not print in the output (comma separated or one per
-    /// '--ignore' argument)
-    #[arg(short, long, value_name = "DEPENDENCIES", use_value_delimiter = true)]
+    /// '--ignore' argument, accepts globs and 're:' prefixed regexes, scoped
+    /// to members as 'member:dep' or to parents as 'parent->dep')
+    #[arg(
+        short,
+        long,
+        value_name = "DEPENDENCIES",
+        use_value_delimiter = true,
+        value_parser = parse_ignore_rule
+    )]
     pub ignore: Vec<String>,
     /// Dependencies to exclude from building (comma separated or one per
-    /// '--exclude' argument)
+    /// '--exclude' argument, accepts globs and 're:' prefixed regexes)
     #[arg(
         short = 'x',
         long,
         value_name = "DEPENDENCIES",
-        use_value_delimiter = true
+        use_value_delimiter = true,
+        value_parser = parse_pattern
     )]
     pub exclude: Vec<String>,
     /// Path to the Cargo.toml file to use (Default to Cargo.toml in project
     /// root)
     #[arg(short, long, value_name = "PATH")]
     pub manifest_path: Option<String>,
+    /// Check every workspace found under this directory, skipping files
+    /// ignored by git
+    #[arg(
+        long,
+        value_name = "DIR",
+        conflicts_with_all = ["manifest_path", "interactive"]
+    )]
+    pub recursive: Option<String>,
+    /// Directories to skip with '--recursive', relative to its directory
+    /// (comma separated or one per '--exclude-dir' argument, accepts globs and
+    /// 're:' prefixed regexes)
+    #[arg(
+        long,
+        value_name = "DIRS",
+        use_value_delimiter = true,
+        value_parser = parse_pattern,
+        requires = "recursive"
+    )]
+    pub exclude_dir: Vec<String>,
+    /// Root manifest of the workspace being checked with '--recursive', which
+    /// tags the JSON records reporting on it
+    #[arg(skip)]
+    pub workspace_root: Option<String>,
+    /// Check the dependencies of a published crate, at the given version or
+    /// the latest one, rather than those of a local project
+    #[arg(
+        long = "crate",
+        value_name = "NAME[@VERSION]",
+        value_parser = parse_crate_spec,
+        conflicts_with_all = ["manifest_path", "interactive", "apply_compat", "recursive"]
+    )]
+    pub crate_spec: Option<String>,
     /// Suppresses warnings
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     pub quiet: bool,
     /// Use verbose output
-    #[arg(short, long, action = ArgAction::Count)]
+    #[arg(short, long, global = true, action = ArgAction::Count)]
     pub verbose: u8,
     /// The exit code to return on new versions found
-    #[arg(long, value_name = "NUM", default_value_t = Default::default())]
+    #[arg(long, value_name = "NUM", global = true, default_value_t = Default::default())]
     pub exit_code: i32,
     /// Packages to inspect for updates (comma separated or one per --packages'
-    /// argument)
-    #[arg(short, long, value_name = "PKGS", use_value_delimiter = true)]
+    /// argument, accepts globs and 're:' prefixed regexes)
+    #[arg(
+        short,
+        long,
+        value_name = "PKGS",
+        use_value_delimiter = true,
+        value_parser = parse_pattern
+    )]
     pub packages: Vec<String>,
     /// Package to treat as the root package
     #[arg(short, long)]
@@ -98,20 +213,171 @@ pub struct Options {
     pub workspace_only: bool,
     /// Run without accessing the network (useful for testing w/ local
     /// registries)
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     pub offline: bool,
+    /// Require the project's Cargo.lock to be up to date
+    #[arg(long)]
+    pub locked: bool,
+    /// Require the project's Cargo.lock to be up to date and run without
+    /// accessing the network
+    #[arg(long)]
+    pub frozen: bool,
+    /// Override a configuration value (KEY=VALUE or a path to a TOML file)
+    #[arg(long, value_name = "KEY=VALUE", global = true)]
+    pub config: Vec<String>,
+    /// Unstable (nightly-only) flags to Cargo
+    #[arg(short = 'Z', value_name = "FLAG", global = true)]
+    pub unstable_flags: Vec<String>,
+    /// Dependency kinds to check (comma separated or one per '--kind'
+    /// argument, defaults to all kinds)
+    #[arg(long, value_enum, ignore_case = true, use_value_delimiter = true)]
+    pub kind: Vec<Kind>,
+    /// Show repository and homepage links of outdated direct dependencies
+    /// along with their packaged changelog entries since the project version
+    #[arg(long)]
+    pub changelog: bool,
+    /// Explain why dependencies are reported as removed
+    #[arg(long)]
+    pub explain: bool,
+    /// Compare path dependencies with the latest version of the package of
+    /// the same name published on the registry
+    #[arg(long)]
+    pub check_path_deps: bool,
+    /// List `[patch]` entries along with the latest upstream versions and
+    /// whether they are still used or can be dropped
+    #[arg(long)]
+    pub check_patches: bool,
+    /// Flag wildcard, exact, stale and pre-release requirements of direct
+    /// dependencies and suggest replacements
+    #[arg(long)]
+    pub check_requirements: bool,
+    /// Minor versions a lower bound may lag behind the locked version before
+    /// '--check-requirements' flags it [default: 3]
+    #[arg(long, value_name = "NUM", requires = "check_requirements")]
+    pub max_minor_lag: Option<u64>,
+    /// List dependencies declared by several workspace members along with
+    /// their requirements and resolved versions
+    #[arg(long)]
+    pub check_consistency: bool,
+    /// List crates which taking the compatible or latest upgrades would
+    /// introduce or drop
+    #[arg(long)]
+    pub supply_chain: bool,
+    /// Plan the upgrades of outdated direct dependencies as ordered batches
+    /// of upgrades which must move together
+    #[arg(long, conflicts_with = "interactive")]
+    pub plan: bool,
+    /// Build a copy of the project with each batch of the upgrade plan
+    /// applied, to tell drop-in upgrades from those breaking the build
+    #[arg(long, value_enum, ignore_case = true, conflicts_with = "interactive")]
+    pub verify: Option<Verify>,
+    /// Print the `cargo update --precise` commands applying the compatible
+    /// updates rather than the report
+    #[arg(long, conflicts_with_all = ["interactive", "minimal"])]
+    pub update_script: bool,
+    /// Update Cargo.lock to the compatible versions rather than printing the
+    /// report, then print how the locked packages changed
+    #[arg(long, conflicts_with_all = ["interactive", "minimal", "update_script"])]
+    pub apply_compat: bool,
+    /// Only apply the compatible updates of these crates (comma separated or
+    /// one per '--only' argument, accepts globs and 're:' prefixed regexes)
+    #[arg(
+        long,
+        value_name = "CRATES",
+        use_value_delimiter = true,
+        value_parser = parse_pattern,
+        requires = "apply_compat"
+    )]
+    pub only: Vec<String>,
+    /// Pin direct dependencies to the given versions and report how the
+    /// resolved packages change rather than looking for updates
+    #[arg(
+        long,
+        value_name = "CRATE=VERSION",
+        conflicts_with_all = ["interactive", "minimal"],
+        value_parser = parse_assumption
+    )]
+    pub assume: Vec<String>,
+    /// Check that dependencies resolve at the lowest versions allowed by
+    /// their requirements rather than looking for updates
+    #[arg(long, conflicts_with = "interactive")]
+    pub minimal: bool,
+    /// Review outdated direct dependencies and select upgrades to apply to
+    /// the manifests
+    #[arg(long)]
+    pub interactive: bool,
+    /// Read interactive commands from a file rather than stdin
+    #[arg(long, value_name = "PATH", requires = "interactive")]
+    pub script: Option<String>,
+    #[command(subcommand)]
+    pub command: Option<OutdatedCommand>,
 }
 
 impl Options {
-    pub fn all_features(&self) -> bool { self.features.is_empty() }
+    /// Features requested for the workspace member `member`, including
+    /// `default` unless disabled. Features scoped to other members are left
+    /// out while dependency features (`dep/feature`) are kept as is.
+    pub fn member_features(&self, member: &str, members: &[&str]) -> Vec<&str> {
+        let mut features: Vec<&str> = self
+            .features
+            .iter()
+            .filter_map(|feature| match feature.split_once('/') {
+                Some((scope, feature)) if scope == member => Some(feature),
+                Some((scope, _)) if members.contains(&scope) => None,
+                _ => Some(feature.as_str()),
+            })
+            .collect();
+        if !self.no_default_features {
+            features.push("default");
+        }
+        features
+    }
 
-    pub fn no_default_features(&self) -> bool {
-        !(self.features.is_empty() || self.features.contains(&"default".to_owned()))
+    /// Versions assumed with `--assume`, by crate name
+    pub fn assumptions(&self) -> BTreeMap<String, Version> {
+        self.assume
+            .iter()
+            .filter_map(|assumption| assumption.split_once('='))
+            .filter_map(|(name, version)| Some((name.to_owned(), Version::parse(version).ok()?)))
+            .collect()
     }
 
-    pub fn locked(&self) -> bool { false }
+    /// Whether dependencies of the given kind should be checked
+    pub fn includes_kind(&self, kind: DepKind) -> bool {
+        let kind = match kind {
+            DepKind::Normal => Kind::Normal,
+            DepKind::Build => Kind::Build,
+            DepKind::Development => Kind::Dev,
+        };
+        self.kind.is_empty() || self.kind.contains(&kind)
+    }
+}
 
-    pub fn frozen(&self) -> bool { false }
+/// Validate a `CRATE=VERSION` assumption
+fn parse_assumption(assumption: &str) -> Result<String, String> {
+    let Some((name, version)) = assumption.split_once('=') else {
+        return Err(format!("expected CRATE=VERSION, got `{assumption}`"));
+    };
+    if name.is_empty() {
+        return Err(format!("missing crate name in `{assumption}`"));
+    }
+    Version::parse(version).map_err(|e| format!("invalid version `{version}`: {e}"))?;
+    Ok(assumption.to_owned())
+}
+
+/// Validate a `NAME[@VERSION]` crate spec
+fn parse_crate_spec(spec: &str) -> Result<String, String> {
+    let (name, version) = match spec.split_once('@') {
+        Some((name, version)) => (name, Some(version)),
+        None => (spec, None),
+    };
+    if name.is_empty() {
+        return Err(format!("missing crate name in `{spec}`"));
+    }
+    if let Some(version) = version {
+        Version::parse(version).map_err(|e| format!("invalid version `{version}`: {e}"))?;
+    }
+    Ok(spec.to_owned())
 }
 
 pub fn parse() -> Options {
@@ -131,11 +397,18 @@ fn split_elem_by_ascii_whitespace(slice: &[String]) -> Vec<String> {
 
 fn try_parse_from(args: impl IntoIterator<Item = impl Into<OsString> + Clone>) -> Result<Options> {
     let CargoCommand::Outdated(mut opts) = Cargo::try_parse_from(args)?.command;
+    if let Some(OutdatedCommand::Unexpected(args)) = &opts.command {
+        return Err(Options::command().bin_name("cargo outdated").error(
+            ErrorKind::UnknownArgument,
+            format!("unexpected argument '{}' found", args[0]),
+        ));
+    }
 
     opts.exclude = split_elem_by_ascii_whitespace(&opts.exclude);
     opts.features = split_elem_by_ascii_whitespace(&opts.features);
     opts.ignore = split_elem_by_ascii_whitespace(&opts.ignore);
     opts.packages = split_elem_by_ascii_whitespace(&opts.packages);
+    opts.only = split_elem_by_ascii_whitespace(&opts.only);
 
     if opts.root_deps_only {
         opts.depth = Some(1);
@@ -223,6 +496,64 @@ mod test {
         assert_eq!(correct, opts5);
     }
 
+    #[test]
+    fn feature_flags() {
+        let opts = options(&["--all-features", "--no-default-features"]);
+        let correct = Options {
+            all_features: true,
+            no_default_features: true,
+            ..Options::default()
+        };
+
+        assert_eq!(correct, opts);
+    }
+
+    #[test]
+    fn member_features() {
+        let opts = options(&["--features", "one,member-a/two,member-b/three,serde/derive"]);
+        let members = ["member-a", "member-b"];
+
+        assert_eq!(
+            vec!["one", "two", "serde/derive", "default"],
+            opts.member_features("member-a", &members)
+        );
+        let opts = Options {
+            no_default_features: true,
+            ..opts
+        };
+        assert_eq!(
+            vec!["one", "three", "serde/derive"],
+            opts.member_features("member-b", &members)
+        );
+    }
+
+    #[test]
+    fn cargo_config() {
+        let opts = options(&[
+            "--frozen",
+            "--locked",
+            "--config",
+            "net.retry=5",
+            "--config",
+            "registries.mirror.index='https://example.com'",
+            "-Z",
+            "sparse-registry",
+            "-Zgitoxide",
+        ]);
+        let correct = Options {
+            frozen: true,
+            locked: true,
+            config: vec![
+                "net.retry=5".into(),
+                "registries.mirror.index='https://example.com'".into(),
+            ],
+            unstable_flags: vec!["sparse-registry".into(), "gitoxide".into()],
+            ..Options::default()
+        };
+
+        assert_eq!(correct, opts);
+    }
+
     #[test]
     fn features_fail() {
         let res = options_fail(&["--features", "one", "two"]);
@@ -327,6 +658,258 @@ mod test {
         );
     }
 
+    #[test]
+    fn patterns() {
+        let opts1 = options(&["--ignore", "aws-sdk-*,re:^windows[-_]", "-p", "serde*"]);
+        let correct = Options {
+            ignore: vec!["aws-sdk-*".into(), "re:^windows[-_]".into()],
+            packages: vec!["serde*".into()],
+            ..Options::default()
+        };
+
+        assert_eq!(correct, opts1);
+    }
+
+    #[test]
+    fn patterns_fail() {
+        let res1 = options_fail(&["--exclude", "foo[bar"]);
+        let res2 = options_fail(&["--ignore", "re:(foo"]);
+        assert_eq!(
+            res1.as_ref().unwrap_err().kind(),
+            clap::error::ErrorKind::ValueValidation,
+        );
+        assert_eq!(
+            res2.as_ref().unwrap_err().kind(),
+            clap::error::ErrorKind::ValueValidation,
+        );
+    }
+
+    #[test]
+    fn scoped_ignore() {
+        let opts1 = options(&["--ignore", "member-a:serde,clap->libc"]);
+        let correct = Options {
+            ignore: vec!["member-a:serde".into(), "clap->libc".into()],
+            ..Options::default()
+        };
+
+        assert_eq!(correct, opts1);
+    }
+
+    #[test]
+    fn scoped_ignore_fail() {
+        let res = options_fail(&["--ignore", "member-a:"]);
+        assert_eq!(
+            res.as_ref().unwrap_err().kind(),
+            clap::error::ErrorKind::ValueValidation,
+        );
+    }
+
+    #[test]
+    fn interactive() {
+        let opts1 = options(&["--interactive", "--script", "commands.txt"]);
+        let correct = Options {
+            interactive: true,
+            script: Some("commands.txt".into()),
+            ..Options::default()
+        };
+
+        assert_eq!(correct, opts1);
+    }
+
+    #[test]
+    fn explain() {
+        let opts1 = options(&["--explain", "--depth", "2"]);
+        let correct = Options {
+            explain: true,
+            depth: Some(2),
+            ..Options::default()
+        };
+
+        assert_eq!(correct, opts1);
+    }
+
+    #[test]
+    fn checks() {
+        let opts1 = options(&[
+            "--check-path-deps",
+            "--check-patches",
+            "--check-requirements",
+            "--max-minor-lag",
+            "1",
+            "--check-consistency",
+            "--supply-chain",
+        ]);
+        let correct = Options {
+            check_path_deps: true,
+            check_patches: true,
+            check_requirements: true,
+            max_minor_lag: Some(1),
+            check_consistency: true,
+            supply_chain: true,
+            ..Options::default()
+        };
+
+        assert_eq!(correct, opts1);
+    }
+
+    #[test]
+    fn group_by() {
+        let opts1 = options(&["--workspace", "--group-by", "dependency"]);
+        let correct = Options {
+            workspace: true,
+            group_by: GroupBy::Dependency,
+            ..Options::default()
+        };
+
+        assert_eq!(correct, opts1);
+        assert_eq!(GroupBy::Member, Options::default().group_by);
+    }
+
+    #[test]
+    fn plan_fail() {
+        let res = options_fail(&["--plan", "--interactive"]);
+        assert_eq!(
+            res.as_ref().unwrap_err().kind(),
+            clap::error::ErrorKind::ArgumentConflict,
+        );
+    }
+
+    #[test]
+    fn assume() {
+        let opts1 = options(&["--assume", "reqwest=0.13.0", "--assume", "rand=0.9.0"]);
+        let correct = Options {
+            assume: vec!["reqwest=0.13.0".into(), "rand=0.9.0".into()],
+            ..Options::default()
+        };
+
+        assert_eq!(correct, opts1);
+        assert_eq!(
+            Some(&Version::new(0, 13, 0)),
+            opts1.assumptions().get("reqwest")
+        );
+    }
+
+    #[test]
+    fn assume_fail() {
+        for assumption in ["reqwest", "=0.13.0", "reqwest=0.13"] {
+            let res = options_fail(&["--assume", assumption]);
+            assert_eq!(
+                res.as_ref().unwrap_err().kind(),
+                clap::error::ErrorKind::ValueValidation,
+            );
+        }
+    }
+
+    #[test]
+    fn verify() {
+        let opts1 = options(&["--verify", "Test"]);
+        let correct = Options {
+            verify: Some(Verify::Test),
+            ..Options::default()
+        };
+
+        assert_eq!(correct, opts1);
+        assert_eq!("check", Verify::Check.to_string());
+    }
+
+    #[test]
+    fn update_script_fail() {
+        let res = options_fail(&["--update-script", "--interactive"]);
+        assert_eq!(
+            res.as_ref().unwrap_err().kind(),
+            clap::error::ErrorKind::ArgumentConflict,
+        );
+    }
+
+    #[test]
+    fn apply_compat() {
+        let opts1 = options(&["--apply-compat", "--only", "log,rand_*"]);
+        let correct = Options {
+            apply_compat: true,
+            only: vec!["log".into(), "rand_*".into()],
+            ..Options::default()
+        };
+
+        assert_eq!(correct, opts1);
+        assert_eq!(
+            options_fail(&["--only", "log"]).unwrap_err().kind(),
+            clap::error::ErrorKind::MissingRequiredArgument,
+        );
+    }
+
+    #[test]
+    fn crate_spec() {
+        let opts1 = options(&["--crate", "serde_json@1.0.100"]);
+        let correct = Options {
+            crate_spec: Some("serde_json@1.0.100".into()),
+            ..Options::default()
+        };
+
+        assert_eq!(correct, opts1);
+        for spec in ["@1.0.100", "serde_json@1.0"] {
+            assert_eq!(
+                options_fail(&["--crate", spec]).unwrap_err().kind(),
+                clap::error::ErrorKind::ValueValidation,
+            );
+        }
+        assert_eq!(
+            options_fail(&["--crate", "serde_json", "--manifest-path", "Cargo.toml"])
+                .unwrap_err()
+                .kind(),
+            clap::error::ErrorKind::ArgumentConflict,
+        );
+    }
+
+    #[test]
+    fn recursive() {
+        let opts1 = options(&["--recursive", "repo", "--exclude-dir", "vendor,fixtures/*"]);
+        let correct = Options {
+            recursive: Some("repo".into()),
+            exclude_dir: vec!["vendor".into(), "fixtures/*".into()],
+            ..Options::default()
+        };
+
+        assert_eq!(correct, opts1);
+        assert_eq!(
+            options_fail(&["--recursive", "repo", "--manifest-path", "Cargo.toml"])
+                .unwrap_err()
+                .kind(),
+            clap::error::ErrorKind::ArgumentConflict,
+        );
+    }
+
+    #[test]
+    fn installed() {
+        let opts1 = options(&["installed", "--format", "json"]);
+        let opts2 = options(&["--offline", "installed"]);
+        let opts3 = options(&["installed", "-Z", "gitoxide", "--config", "net.retry=5"]);
+
+        assert_eq!(Some(OutdatedCommand::Installed), opts1.command);
+        assert_eq!(Format::Json, opts1.format);
+        assert!(opts2.offline);
+        assert_eq!(Some(OutdatedCommand::Installed), opts2.command);
+        assert_eq!(vec!["gitoxide".to_owned()], opts3.unstable_flags);
+        assert_eq!(vec!["net.retry=5".to_owned()], opts3.config);
+    }
+
+    #[test]
+    fn minimal_fail() {
+        let res = options_fail(&["--minimal", "--interactive"]);
+        assert_eq!(
+            res.as_ref().unwrap_err().kind(),
+            clap::error::ErrorKind::ArgumentConflict,
+        );
+    }
+
+    #[test]
+    fn script_fail() {
+        let res = options_fail(&["--script", "commands.txt"]);
+        assert_eq!(
+            res.as_ref().unwrap_err().kind(),
+            clap::error::ErrorKind::MissingRequiredArgument,
+        );
+    }
+
     #[test]
     fn format_case() {
         let opts1 = options(&["--format", "JsOn"]);
@@ -359,6 +942,43 @@ mod test {
         assert_eq!(correct, opts1);
     }
 
+    #[test]
+    fn kind() {
+        let opts1 = options(&["--kind", "normal,build"]);
+        let opts2 = options(&["--kind", "Normal", "--kind", "build"]);
+        let correct = Options {
+            kind: vec![Kind::Normal, Kind::Build],
+            ..Options::default()
+        };
+
+        assert_eq!(correct, opts1);
+        assert_eq!(correct, opts2);
+        assert!(opts1.includes_kind(DepKind::Build));
+        assert!(!opts1.includes_kind(DepKind::Development));
+        assert!(Options::default().includes_kind(DepKind::Development));
+    }
+
+    #[test]
+    fn kind_alias() {
+        let opts1 = options(&["--kind", "development"]);
+        let correct = Options {
+            kind: vec![Kind::Dev],
+            ..Options::default()
+        };
+
+        assert_eq!(correct, opts1);
+    }
+
+    #[test]
+    fn kind_unknown() {
+        let res = options_fail(&["--kind", "foobar"]);
+        assert!(res.is_err());
+        assert_eq!(
+            res.as_ref().unwrap_err().kind(),
+            clap::error::ErrorKind::InvalidValue,
+        );
+    }
+
     #[test]
     fn color_unknown() {
         let res = options_fail(&["--color", "foobar"]);
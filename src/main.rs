@@ -8,9 +8,16 @@ mod macros;
 mod cargo_ops;
 mod cli;
 mod error;
+mod pattern;
 
-use std::collections::HashSet;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    fs::File,
+    io::{self, BufReader, IsTerminal},
+    path::Path,
+};
 
+use anyhow::anyhow;
 use cargo::{
     core::{Workspace, shell::Verbosity},
     util::{
@@ -22,9 +29,21 @@ use cargo::{
 };
 
 use crate::{
-    cargo_ops::{ElaborateWorkspace, TempProject},
-    cli::{Format, Options},
+    cargo_ops::{
+        ElaborateWorkspace, MinimalReport, PackageDelta, PreciseUpdate, TempProject,
+        WorkspaceRoots, group_by_dependency, installed_packages, installed_updates, patch_statuses,
+        path_dependencies, print_groups, print_groups_json, print_installed, print_installed_json,
+        print_json, print_patches, print_patches_json, print_path_dependencies,
+        print_path_dependencies_json, print_plan, print_plan_json, print_requirements,
+        print_requirements_json, print_shared_dependencies, print_shared_dependencies_json,
+        print_supply_chain, print_supply_chain_json, print_update_script, print_update_script_json,
+        print_verifications, print_verifications_json, resolves_with, run_session, run_tui,
+        scratch_project, shared_dependencies, supply_chain_changes, upgrade_plan, verify_batches,
+        workspace_roots,
+    },
+    cli::{Format, GroupBy, Options, OutdatedCommand},
     error::OutdatedError,
+    pattern::{PatternSet, scope_to_member},
 };
 
 fn main() {
@@ -72,7 +91,7 @@ fn main() {
 }
 
 /// executes the cargo-outdate command with the cargo configuration and options
-pub fn execute(options: Options, context: &mut GlobalContext) -> CargoResult<i32> {
+pub fn execute(mut options: Options, context: &mut GlobalContext) -> CargoResult<i32> {
     // Check if $CARGO_HOME is set before capturing the config environment
     // if it is, set it in the configure options
     let cargo_home_path = std::env::var_os("CARGO_HOME").map(std::path::PathBuf::from);
@@ -84,19 +103,34 @@ pub fn execute(options: Options, context: &mut GlobalContext) -> CargoResult<i32
         options.verbose.into(),
         options.quiet,
         Some(&options.color.to_string().to_ascii_lowercase()),
-        options.frozen(),
-        options.locked(),
+        options.frozen,
+        options.locked,
         options.offline,
         &cargo_home_path,
-        &[],
-        &[],
+        &options.unstable_flags,
+        &options.config,
     )?;
     debug!(context, format!("options: {options:?}"));
 
+    if options.command == Some(OutdatedCommand::Installed) {
+        return execute_installed(&options, context);
+    }
+    if let Some(dir) = options.recursive.take() {
+        return execute_recursive(Path::new(&dir), options, context);
+    }
+
     verbose!(context, "Parsing...", "current workspace");
+    // a project depending on the published crate to check, if any
+    let scratch = options
+        .crate_spec
+        .as_deref()
+        .map(scratch_project)
+        .transpose()?;
     // the Cargo.toml that we are actually working on
     let mut manifest_abspath: std::path::PathBuf;
-    let curr_manifest = if let Some(ref manifest_path) = options.manifest_path {
+    let curr_manifest = if let Some(ref scratch) = scratch {
+        scratch.path().join("Cargo.toml")
+    } else if let Some(ref manifest_path) = options.manifest_path {
         manifest_abspath = manifest_path.into();
         if manifest_abspath.is_relative() {
             verbose!(context, "Resolving...", "absolute path of manifest");
@@ -107,6 +141,7 @@ pub fn execute(options: Options, context: &mut GlobalContext) -> CargoResult<i32
         find_root_manifest_for_wd(context.cwd())?
     };
     let curr_workspace = Workspace::new(&curr_manifest, context)?;
+    options.ignore.extend(metadata_ignores(&curr_workspace)?);
     verbose!(context, "Resolving...", "current workspace");
     if options.verbose == 0 {
         context.shell().set_verbosity(Verbosity::Quiet);
@@ -118,6 +153,24 @@ pub fn execute(options: Options, context: &mut GlobalContext) -> CargoResult<i32
         context.shell().set_verbosity(Verbosity::Normal);
     }
 
+    if options.minimal {
+        return execute_minimal(
+            &ela_curr,
+            &curr_manifest.to_string_lossy(),
+            &options,
+            context,
+        );
+    }
+
+    if !options.assume.is_empty() {
+        return execute_assume(
+            &ela_curr,
+            &curr_manifest.to_string_lossy(),
+            &options,
+            context,
+        );
+    }
+
     verbose!(context, "Parsing...", "compat workspace");
     let mut skipped = HashSet::new();
     let compat_proj =
@@ -147,19 +200,134 @@ pub fn execute(options: Options, context: &mut GlobalContext) -> CargoResult<i32
         compat_proj.temp_dir.path(),
         &ela_curr,
         &mut skipped,
+        &HashSet::new(),
     )?;
+    let requirement_lints = latest_proj.requirement_lints();
     verbose!(context, "Updating...", "latest workspace");
-    latest_proj.cargo_update()?;
+    let mut unresolvable = BTreeMap::new();
+    let retry_proj;
+    let latest_proj = match latest_proj.cargo_update() {
+        Ok(()) => &latest_proj,
+        Err(error) => {
+            // hold back the upgrades making the latest workspace unresolvable
+            verbose!(context, "Bisecting...", "unresolvable latest upgrades");
+            unresolvable =
+                TempProject::bisect_unresolvable(latest_proj.rewritten(), error, |held_back| {
+                    resolves_with(
+                        &ela_curr,
+                        &curr_manifest.to_string_lossy(),
+                        &options,
+                        |proj| {
+                            proj.write_manifest_latest(
+                                curr_workspace.root(),
+                                compat_proj.temp_dir.path(),
+                                &ela_curr,
+                                &mut skipped,
+                                held_back,
+                            )
+                        },
+                    )
+                })?;
+            retry_proj =
+                TempProject::from_workspace(&ela_curr, &curr_manifest.to_string_lossy(), &options)?;
+            retry_proj.write_manifest_latest(
+                curr_workspace.root(),
+                compat_proj.temp_dir.path(),
+                &ela_curr,
+                &mut skipped,
+                &unresolvable.keys().cloned().collect(),
+            )?;
+            verbose!(context, "Updating...", "latest workspace");
+            retry_proj.cargo_update()?;
+            &retry_proj
+        }
+    };
     verbose!(context, "Resolving...", "latest workspace");
     let latest_workspace = latest_proj.workspace.borrow();
-    let ela_latest = ElaborateWorkspace::from_workspace(
+    let mut ela_latest = ElaborateWorkspace::from_workspace(
         latest_workspace
             .as_ref()
             .ok_or(OutdatedError::CannotElaborateWorkspace)?,
         &options,
     )?;
+    ela_latest.unresolvable = unresolvable;
+
+    if options.interactive {
+        let roots = ela_curr.report_roots(&options)?;
+        let mut upgrades = vec![];
+        for root in roots {
+            ela_curr.resolve_status(&ela_compat, &ela_latest, &options, context, root, &skipped)?;
+            upgrades.extend(ela_curr.direct_upgrades(root));
+        }
+        if upgrades.is_empty() {
+            println!("All dependencies are up to date, yay!");
+            return Ok(0);
+        }
+        let workspace_manifest = curr_workspace.root_manifest();
+        let applied = match options.script {
+            Some(ref script) => {
+                let script = BufReader::new(File::open(script)?);
+                run_session(&mut upgrades, workspace_manifest, script, &mut io::stdout())?
+            }
+            None if io::stdin().is_terminal() && io::stdout().is_terminal() => {
+                run_tui(&mut upgrades, workspace_manifest)?
+            }
+            None => run_session(
+                &mut upgrades,
+                workspace_manifest,
+                io::stdin().lock(),
+                &mut io::stdout(),
+            )?,
+        };
+        // the upgrades left out of the manifests are still outdated
+        let unapplied = upgrades
+            .iter()
+            .filter(|upgrade| !applied || upgrade.selected_version().is_none())
+            .count();
+        return Ok(unapplied as i32);
+    }
 
-    if ela_curr.workspace_mode {
+    if options.update_script || options.apply_compat {
+        let mut updates = BTreeMap::new();
+        for root in ela_curr.report_roots(&options)? {
+            ela_curr.resolve_status(&ela_compat, &ela_latest, &options, context, root, &skipped)?;
+            updates.extend(ela_curr.compat_updates(root));
+        }
+        let only = PatternSet::new(&options.only)?;
+        let resolved: Vec<_> = ela_curr.pkgs.keys().copied().collect();
+        let updates: Vec<_> = updates
+            .iter()
+            .filter(|(id, _)| only.is_empty() || only.matches(id.name().as_str()))
+            .map(|(&id, precise)| PreciseUpdate::new(id, precise, &resolved))
+            .collect();
+        if options.apply_compat {
+            return apply_compat(&ela_curr, &updates, &options);
+        }
+        match options.format {
+            Format::List => print_update_script(&updates)?,
+            Format::Json => print_update_script_json(&updates, options.workspace_root.as_deref())?,
+        }
+        return Ok(updates.len() as i32);
+    }
+
+    let count = if options.group_by == GroupBy::Dependency {
+        let roots = ela_curr.report_roots(&options)?;
+        let mut uses = vec![];
+        for root in roots {
+            ela_curr.resolve_status(&ela_compat, &ela_latest, &options, context, root, &skipped)?;
+            uses.extend(ela_curr.outdated_uses(&options, root, &skipped)?);
+        }
+        let groups = group_by_dependency(uses);
+        verbose!(
+            context,
+            "Printing...",
+            "Package status grouped by dependency"
+        );
+        match options.format {
+            Format::List => print_groups(&groups)?,
+            Format::Json => print_groups_json(&groups, options.workspace_root.as_deref())?,
+        }
+    } else if ela_curr.workspace_mode {
         let mut sum = 0;
         match options.format {
             Format::List => verbose!(context, "Printing...", "Package status in list format"),
@@ -187,7 +355,7 @@ pub fn execute(options: Options, context: &mut GlobalContext) -> CargoResult<i32
         if sum == 0 && matches!(options.format, Format::List) {
             println!("All dependencies are up to date, yay!");
         }
-        Ok(sum)
+        sum
     } else {
         verbose!(context, "Resolving...", "package status");
         let root = ela_curr.determine_root(&options)?;
@@ -204,6 +372,315 @@ pub fn execute(options: Options, context: &mut GlobalContext) -> CargoResult<i32
             }
         }
 
-        Ok(count)
+        count
+    };
+
+    if options.check_patches {
+        let patches = patch_statuses(&ela_curr)?;
+        match options.format {
+            Format::List => print_patches(&patches)?,
+            Format::Json => print_patches_json(&patches, options.workspace_root.as_deref())?,
+        }
+    }
+    match options.format {
+        Format::List => print_requirements(&requirement_lints)?,
+        Format::Json => {
+            print_requirements_json(&requirement_lints, options.workspace_root.as_deref())?
+        }
+    }
+    if options.check_consistency {
+        let shared = shared_dependencies(&ela_curr, &options);
+        match options.format {
+            Format::List => print_shared_dependencies(&shared)?,
+            Format::Json => {
+                print_shared_dependencies_json(&shared, options.workspace_root.as_deref())?
+            }
+        }
+    }
+    if options.supply_chain {
+        let changes = supply_chain_changes(&ela_curr, &ela_compat, &ela_latest);
+        match options.format {
+            Format::List => print_supply_chain(&changes)?,
+            Format::Json => print_supply_chain_json(&changes, options.workspace_root.as_deref())?,
+        }
+    }
+    if options.plan || options.verify.is_some() {
+        let roots = ela_curr.report_roots(&options)?;
+        let mut upgrades = vec![];
+        for root in roots {
+            ela_curr.resolve_status(&ela_compat, &ela_latest, &options, context, root, &skipped)?;
+            upgrades.extend(ela_curr.direct_upgrades(root));
+        }
+        let batches = upgrade_plan(&ela_compat, &ela_latest, &upgrades);
+        if options.plan {
+            match options.format {
+                Format::List => print_plan(&batches)?,
+                Format::Json => print_plan_json(&batches, options.workspace_root.as_deref())?,
+            }
+        }
+        let root = curr_workspace.root();
+        let verifications = verify_batches(
+            root,
+            curr_manifest.strip_prefix(root)?,
+            &batches,
+            &upgrades,
+            &options,
+            context,
+        )?;
+        match options.format {
+            Format::List => print_verifications(&verifications)?,
+            Format::Json => {
+                print_verifications_json(&verifications, options.workspace_root.as_deref())?
+            }
+        }
+    }
+    if options.check_path_deps {
+        let path_deps = path_dependencies(&ela_curr)?;
+        match options.format {
+            Format::List => print_path_dependencies(&path_deps)?,
+            Format::Json => {
+                print_path_dependencies_json(&path_deps, options.workspace_root.as_deref())?
+            }
+        }
+    }
+
+    Ok(count)
+}
+
+/// Run the analysis for every workspace found under `dir`, returning the
+/// combined count. Workspaces failing to be analysed are reported and
+/// skipped, failing the whole run once the others are done.
+fn execute_recursive(
+    dir: &Path,
+    options: Options,
+    context: &mut GlobalContext,
+) -> CargoResult<i32> {
+    verbose!(context, "Discovering...", "workspaces");
+    let WorkspaceRoots { roots, failed } = workspace_roots(dir, &options.exclude_dir, context)?;
+    // manifests failing to load count as workspaces which were not checked
+    let total = roots.len() + failed.len();
+    let mut failed = failed.len();
+    let mut sum = 0;
+    for (i, root) in roots.iter().enumerate() {
+        let root = root.to_string_lossy().into_owned();
+        if matches!(options.format, Format::List) {
+            if i > 0 {
+                println!();
+            }
+            println!("{root}\n================");
+        }
+        let result = execute(
+            Options {
+                manifest_path: Some(root.clone()),
+                workspace_root: Some(root.clone()),
+                ..options.clone()
+            },
+            context,
+        );
+        match result {
+            Ok(count) => sum += count,
+            Err(e) => {
+                failed += 1;
+                context.shell().set_verbosity(Verbosity::Normal);
+                context
+                    .shell()
+                    .warn(format!("failed to check {root}: {e:#}"))?;
+            }
+        }
+    }
+    if matches!(options.format, Format::List) {
+        println!(
+            "\n{} outdated dependencies across {} workspaces",
+            sum,
+            roots.len()
+        );
+    }
+    if failed > 0 {
+        return Err(anyhow!(
+            "failed to check {} of {} workspaces",
+            failed,
+            total
+        ));
+    }
+    Ok(sum)
+}
+
+/// Check the packages installed with `cargo install` for newer versions
+fn execute_installed(options: &Options, context: &GlobalContext) -> CargoResult<i32> {
+    verbose!(context, "Parsing...", "installed packages");
+    let packages = installed_packages(context.home().as_path_unlocked())?;
+    verbose!(context, "Querying...", "installed packages");
+    let updates = installed_updates(context, &packages)?;
+    match options.format {
+        Format::List => print_installed(&updates)?,
+        Format::Json => print_installed_json(&updates)?,
+    }
+    Ok(updates.len() as i32)
+}
+
+/// Check that direct dependencies resolve at the lowest versions allowed by
+/// their requirements, and transitive dependencies at theirs too
+fn execute_minimal(
+    ela_curr: &ElaborateWorkspace<'_>,
+    curr_manifest: &str,
+    options: &Options,
+    context: &GlobalContext,
+) -> CargoResult<i32> {
+    let root = ela_curr.workspace.root();
+    let mut skipped = HashSet::new();
+    verbose!(context, "Parsing...", "minimal workspace");
+    let minimal_proj = TempProject::from_workspace(ela_curr, curr_manifest, options)?;
+    let tmp_root = minimal_proj.temp_dir.path();
+    minimal_proj.write_manifest_minimal(root, tmp_root, ela_curr, &mut skipped, &HashSet::new())?;
+    let pins = minimal_proj.pins();
+    verbose!(context, "Updating...", "minimal workspace");
+    let mut unresolvable = BTreeMap::new();
+    if let Err(error) = minimal_proj.cargo_update() {
+        verbose!(context, "Bisecting...", "unresolvable lower bounds");
+        let names: BTreeSet<String> = pins.iter().map(|pin| pin.name.clone()).collect();
+        unresolvable =
+            TempProject::bisect_unresolvable(names.into_iter().collect(), error, |held_back| {
+                resolves_with(ela_curr, curr_manifest, options, |proj| {
+                    proj.write_manifest_minimal(
+                        ela_curr.workspace.root(),
+                        tmp_root,
+                        ela_curr,
+                        &mut skipped,
+                        held_back,
+                    )
+                })
+            })?;
+    }
+
+    verbose!(context, "Updating...", "minimal transitive workspace");
+    let mut transitive_proj = TempProject::from_workspace(ela_curr, curr_manifest, options)?;
+    transitive_proj.resolve_minimal_versions()?;
+    transitive_proj.write_manifest_minimal(
+        root,
+        tmp_root,
+        ela_curr,
+        &mut skipped,
+        &unresolvable.keys().cloned().collect(),
+    )?;
+    let transitive_error = transitive_proj
+        .cargo_update()
+        .err()
+        .map(|e| format!("{e:#}"));
+
+    let report = MinimalReport {
+        pins,
+        unresolvable,
+        transitive_error,
+    };
+    match options.format {
+        Format::List => report.print_list()?,
+        Format::Json => report.print_json(options.workspace_root.as_deref())?,
+    }
+    Ok(report.count())
+}
+
+/// Pin the assumed direct dependencies to the given versions and report how
+/// the resolved packages change
+fn execute_assume(
+    ela_curr: &ElaborateWorkspace<'_>,
+    curr_manifest: &str,
+    options: &Options,
+    context: &GlobalContext,
+) -> CargoResult<i32> {
+    let assumed = options.assumptions();
+    let mut skipped = HashSet::new();
+    verbose!(context, "Parsing...", "assumed workspace");
+    let assumed_proj = TempProject::from_workspace(ela_curr, curr_manifest, options)?;
+    assumed_proj.write_manifest_assumed(
+        ela_curr.workspace.root(),
+        assumed_proj.temp_dir.path(),
+        &mut skipped,
+        &assumed,
+    )?;
+    // resolving against the copied lock file only moves what the assumed
+    // versions require
+    verbose!(context, "Resolving...", "assumed workspace");
+    let assumed_workspace = assumed_proj.workspace.borrow();
+    let ela_assumed = ElaborateWorkspace::from_workspace(
+        assumed_workspace
+            .as_ref()
+            .ok_or(OutdatedError::CannotElaborateWorkspace)?,
+        options,
+    )?;
+
+    let delta = PackageDelta::between(ela_curr, &ela_assumed);
+    match options.format {
+        Format::List => {
+            let assumptions: Vec<_> = assumed
+                .iter()
+                .map(|(name, version)| format!("{name} {version}"))
+                .collect();
+            println!("Assuming {}", assumptions.join(", "));
+            if delta.is_empty() {
+                println!("Nothing else changes");
+            } else {
+                delta.print_list("Project", "Assumed")?;
+            }
+        }
+        Format::Json => print_json(&delta, options.workspace_root.as_deref())?,
+    }
+    Ok(delta.count())
+}
+
+/// Update the lock file of the workspace of `ela_curr` with the compatible
+/// `updates`, then print how the locked packages changed
+fn apply_compat(
+    ela_curr: &ElaborateWorkspace<'_>,
+    updates: &[PreciseUpdate],
+    options: &Options,
+) -> CargoResult<i32> {
+    for update in updates {
+        update.apply(ela_curr.workspace)?;
+    }
+    let workspace = Workspace::new(
+        ela_curr.workspace.root_manifest(),
+        ela_curr.workspace.gctx(),
+    )?;
+    let ela_applied = ElaborateWorkspace::from_workspace(&workspace, options)?;
+    let delta = PackageDelta::between(ela_curr, &ela_applied);
+    match options.format {
+        Format::List if delta.is_empty() => println!("Cargo.lock is unchanged"),
+        Format::List => delta.print_list("Before", "After")?,
+        Format::Json => print_json(&delta, options.workspace_root.as_deref())?,
+    }
+    Ok(delta.count())
+}
+
+/// Ignore rules configured in `[workspace.metadata.outdated]`, applied as-is,
+/// and in `[package.metadata.outdated]` of each member, scoped to that member
+fn metadata_ignores(workspace: &Workspace<'_>) -> CargoResult<Vec<String>> {
+    fn ignores(metadata: Option<&toml::Value>) -> Vec<String> {
+        metadata
+            .and_then(|metadata| metadata.get("outdated"))
+            .and_then(|outdated| outdated.get("ignore"))
+            .and_then(toml::Value::as_array)
+            .map(|rules| {
+                rules
+                    .iter()
+                    .filter_map(toml::Value::as_str)
+                    .map(ToOwned::to_owned)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    let mut rules = ignores(workspace.custom_metadata());
+    for member in workspace.members() {
+        for rule in ignores(member.manifest().custom_metadata()) {
+            let rule = scope_to_member(&member.name(), &rule).map_err(|e| {
+                anyhow!(
+                    "invalid [package.metadata.outdated] in {}: {}",
+                    member.manifest_path().to_string_lossy(),
+                    e
+                )
+            })?;
+            rules.push(rule);
+        }
     }
+    Ok(rules)
 }
@@ -0,0 +1,258 @@
+use anyhow::anyhow;
+use cargo::util::CargoResult;
+use globset::{Glob, GlobMatcher};
+use regex::Regex;
+
+/// Prefix marking a pattern as a regular expression rather than a glob
+const REGEX_PREFIX: &str = "re:";
+
+/// A dependency name pattern, either a glob (e.g. `aws-sdk-*`) or a regular
+/// expression prefixed with `re:` (e.g. `re:^windows[-_]`)
+#[derive(Debug)]
+pub enum Pattern {
+    Glob(GlobMatcher),
+    Regex(Regex),
+}
+
+impl Pattern {
+    pub fn new(pattern: &str) -> CargoResult<Pattern> {
+        if let Some(regex) = pattern.strip_prefix(REGEX_PREFIX) {
+            Regex::new(regex)
+                .map(Pattern::Regex)
+                .map_err(|e| anyhow!("invalid regex pattern `{}`: {}", pattern, e))
+        } else {
+            Glob::new(pattern)
+                .map(|glob| Pattern::Glob(glob.compile_matcher()))
+                .map_err(|e| anyhow!("invalid glob pattern `{}`: {}", pattern, e))
+        }
+    }
+
+    pub fn matches(&self, name: &str) -> bool {
+        match self {
+            Pattern::Glob(glob) => glob.is_match(name),
+            Pattern::Regex(regex) => regex.is_match(name),
+        }
+    }
+}
+
+/// A set of patterns matching a name if any of its patterns does
+#[derive(Debug, Default)]
+pub struct PatternSet(Vec<Pattern>);
+
+impl PatternSet {
+    pub fn new(patterns: &[String]) -> CargoResult<PatternSet> {
+        patterns
+            .iter()
+            .map(|p| Pattern::new(p))
+            .collect::<CargoResult<_>>()
+            .map(PatternSet)
+    }
+
+    pub fn is_empty(&self) -> bool { self.0.is_empty() }
+
+    pub fn matches(&self, name: &str) -> bool { self.0.iter().any(|p| p.matches(name)) }
+}
+
+/// An `--ignore` rule, optionally scoped to workspace members
+/// (`member:dependency`) and/or to a parent package (`parent->dependency`);
+/// each part is a pattern on its own
+#[derive(Debug)]
+pub struct IgnoreRule {
+    member: Option<Pattern>,
+    parent: Option<Pattern>,
+    name: Pattern,
+}
+
+impl IgnoreRule {
+    pub fn new(rule: &str) -> CargoResult<IgnoreRule> {
+        // a leading regex owns the rest of the rule, scopes must come first
+        let (member, rest) = match rule.split_once(':') {
+            Some((member, rest)) if !rule.starts_with(REGEX_PREFIX) && !member.contains("->") => {
+                (Some(member), rest)
+            }
+            _ => (None, rule),
+        };
+        let (parent, name) = match rest.split_once("->") {
+            Some((parent, name)) if !rest.starts_with(REGEX_PREFIX) => (Some(parent), name),
+            _ => (None, rest),
+        };
+        if name.is_empty() || member == Some("") || parent == Some("") {
+            return Err(anyhow!(
+                "invalid ignore rule `{}`: empty scope or name",
+                rule
+            ));
+        }
+        Ok(IgnoreRule {
+            member: member.map(Pattern::new).transpose()?,
+            parent: parent.map(Pattern::new).transpose()?,
+            name: Pattern::new(name)?,
+        })
+    }
+
+    /// Whether the rule matches dependency `name` reached from `parent` in
+    /// the report of `member`
+    pub fn matches(&self, member: &str, parent: Option<&str>, name: &str) -> bool {
+        self.name.matches(name)
+            && self.member.as_ref().is_none_or(|m| m.matches(member))
+            && self
+                .parent
+                .as_ref()
+                .is_none_or(|p| parent.is_some_and(|parent| p.matches(parent)))
+    }
+}
+
+/// Scope `rule`, declared in the manifest of `member`, to that member. The
+/// rule may be scoped to a parent package, but not to a member already.
+pub fn scope_to_member(member: &str, rule: &str) -> CargoResult<String> {
+    if IgnoreRule::new(rule)?.member.is_some() {
+        return Err(anyhow!(
+            "invalid ignore rule `{}`: the rules of a member cannot be scoped to a member",
+            rule
+        ));
+    }
+    Ok(format!("{member}:{rule}"))
+}
+
+/// A set of ignore rules matching a dependency if any of its rules does
+#[derive(Debug, Default)]
+pub struct IgnoreSet(Vec<IgnoreRule>);
+
+impl IgnoreSet {
+    pub fn new(rules: &[String]) -> CargoResult<IgnoreSet> {
+        rules
+            .iter()
+            .map(|r| IgnoreRule::new(r))
+            .collect::<CargoResult<_>>()
+            .map(IgnoreSet)
+    }
+
+    pub fn matches(&self, member: &str, parent: Option<&str>, name: &str) -> bool {
+        self.0.iter().any(|r| r.matches(member, parent, name))
+    }
+}
+
+/// Validates a pattern given on the command line, for use as a clap value
+/// parser
+pub fn parse_pattern(pattern: &str) -> Result<String, String> {
+    Pattern::new(pattern)
+        .map(|_| pattern.to_owned())
+        .map_err(|e| e.to_string())
+}
+
+/// Validates an ignore rule given on the command line, for use as a clap
+/// value parser
+pub fn parse_ignore_rule(rule: &str) -> Result<String, String> {
+    IgnoreRule::new(rule)
+        .map(|_| rule.to_owned())
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn set(patterns: &[&str]) -> PatternSet {
+        let patterns: Vec<String> = patterns.iter().map(|&p| p.to_owned()).collect();
+        PatternSet::new(&patterns).unwrap()
+    }
+
+    #[test]
+    fn exact() {
+        let set = set(&["serde"]);
+        assert!(set.matches("serde"));
+        assert!(!set.matches("serde_json"));
+    }
+
+    #[test]
+    fn glob() {
+        let set = set(&["aws-sdk-*", "windows_*"]);
+        assert!(set.matches("aws-sdk-s3"));
+        assert!(set.matches("windows_x86_64_msvc"));
+        assert!(!set.matches("aws-config"));
+        assert!(!set.matches("windows-sys"));
+    }
+
+    #[test]
+    fn regex() {
+        let set = set(&["re:^windows[-_]"]);
+        assert!(set.matches("windows-sys"));
+        assert!(set.matches("windows_aarch64_gnullvm"));
+        assert!(!set.matches("winapi"));
+    }
+
+    fn ignore(rules: &[&str]) -> IgnoreSet {
+        let rules: Vec<String> = rules.iter().map(|&r| r.to_owned()).collect();
+        IgnoreSet::new(&rules).unwrap()
+    }
+
+    #[test]
+    fn ignore_unscoped() {
+        let set = ignore(&["serde*"]);
+        assert!(set.matches("member-a", None, "serde"));
+        assert!(set.matches("member-b", Some("clap"), "serde_json"));
+    }
+
+    #[test]
+    fn ignore_member() {
+        let set = ignore(&["member-a:serde"]);
+        assert!(set.matches("member-a", None, "serde"));
+        assert!(set.matches("member-a", Some("clap"), "serde"));
+        assert!(!set.matches("member-b", None, "serde"));
+    }
+
+    #[test]
+    fn ignore_parent() {
+        let set = ignore(&["clap->re:^term"]);
+        assert!(set.matches("member-a", Some("clap"), "term_size"));
+        assert!(!set.matches("member-a", Some("tokio"), "term_size"));
+        assert!(!set.matches("member-a", None, "term_size"));
+    }
+
+    #[test]
+    fn ignore_member_and_parent() {
+        let set = ignore(&["member-*:clap->libc"]);
+        assert!(set.matches("member-a", Some("clap"), "libc"));
+        assert!(!set.matches("other", Some("clap"), "libc"));
+        assert!(!set.matches("member-a", Some("rand"), "libc"));
+    }
+
+    #[test]
+    fn ignore_regex_unscoped() {
+        let set = ignore(&["re:^a:b->c$"]);
+        assert!(set.matches("member-a", None, "a:b->c"));
+    }
+
+    #[test]
+    fn member_rules() {
+        let rules: Vec<_> = ["serde", "clap->libc", "re:^a:b->c$"]
+            .iter()
+            .map(|rule| scope_to_member("member-a", rule).unwrap())
+            .collect();
+        let set = IgnoreSet::new(&rules).unwrap();
+        assert!(set.matches("member-a", None, "serde"));
+        assert!(!set.matches("member-b", None, "serde"));
+        assert!(set.matches("member-a", Some("clap"), "libc"));
+        assert!(!set.matches("member-a", Some("rand"), "libc"));
+        assert!(!set.matches("member-b", Some("clap"), "libc"));
+        assert!(set.matches("member-a", None, "a:b->c"));
+
+        assert!(scope_to_member("member-a", "member-b:serde").is_err());
+        assert!(scope_to_member("member-a", "member-a:clap->libc").is_err());
+        assert!(scope_to_member("member-a", "->serde").is_err());
+    }
+
+    #[test]
+    fn ignore_invalid() {
+        assert!(IgnoreRule::new("member:").is_err());
+        assert!(IgnoreRule::new(":serde").is_err());
+        assert!(IgnoreRule::new("->serde").is_err());
+        assert!(IgnoreRule::new("member:re:(serde").is_err());
+    }
+
+    #[test]
+    fn empty() {
+        let set = set(&[]);
+        assert!(set.is_empty());
+        assert!(!set.matches("serde"));
+    }
+}